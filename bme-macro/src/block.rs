@@ -0,0 +1,711 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    bracketed, parenthesized, Attribute, Error, GenericArgument, Ident, LitStr, PathArguments,
+    Result, Token, Type,
+};
+
+/// Block types which can be given to the `typ` attribute. Loop blocks
+/// need an `iterate` a block function can't give.
+const BLOCK_TYPES: &[&str] = &["Start", "End", "Static", "Normal", "Comment"];
+
+/// Definition of an execution block as given to `ExecutionBlockHelper!`
+/// or `#[block]`
+pub struct Block {
+    id: u32,
+    name: Ident,
+    typ: Ident,
+    path: syn::Path,
+    next: Option<Vec<Ident>>,
+    pure: bool,
+    error_output: bool,
+    description: Option<String>,
+    inputs: Vec<Input>,
+    outputs: Vec<Output>,
+    /// Set when the block function returns a `Result`, with the error type
+    /// when it isn't the one of the `bme` crate
+    error: Option<Option<Type>>,
+    code: syn::Block,
+}
+
+/// Name, label and description of a data input or output
+pub struct Pin {
+    pub name: Ident,
+    label: Option<LitStr>,
+    description: Option<LitStr>,
+}
+
+impl Pin {
+    /// Creates the pin from its name and `#[pin(label = "..", description = "..")]` attributes
+    pub fn new(name: Ident, attrs: &[Attribute]) -> Result<Pin> {
+        let mut label = None;
+        let mut description = None;
+
+        for attr in attrs {
+            if !attr.path().is_ident("pin") {
+                return Err(Error::new_spanned(
+                    attr,
+                    "unknown attribute, expected `pin`",
+                ));
+            }
+
+            attr.parse_nested_meta(|meta| match meta.path.get_ident() {
+                Some(i) if i == "label" => set(&mut label, i, meta.value()?.parse()?),
+                Some(i) if i == "description" => set(&mut description, i, meta.value()?.parse()?),
+                _ => Err(meta.error("unknown pin attribute, expected `label` or `description`")),
+            })?;
+        }
+
+        Ok(Pin {
+            name,
+            label,
+            description,
+        })
+    }
+
+    /// Generates the `Pin` value for the block definition
+    fn expand(&self, path: &syn::Path) -> TokenStream {
+        let name = self.name.to_string();
+        let label = option(&self.label);
+        let description = option(&self.description);
+
+        quote! {
+            #path::Pin {
+                name: #name,
+                label: #label,
+                description: #description,
+            }
+        }
+    }
+}
+
+/// A data input of the block function, `name: Type`
+pub struct Input {
+    pub pin: Pin,
+    pub typ: Type,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let typ = input.parse()?;
+
+        Ok(Input {
+            pin: Pin::new(name, &attrs)?,
+            typ,
+        })
+    }
+}
+
+/// An output of the block function, either `Type` or `name: Type`
+pub struct Output {
+    pub pin: Option<Pin>,
+    pub typ: Type,
+}
+
+impl Parse for Output {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+
+        let pin = if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let name = input.parse()?;
+            input.parse::<Token![:]>()?;
+            Some(Pin::new(name, &attrs)?)
+        } else if let Some(attr) = attrs.first() {
+            return Err(Error::new_spanned(
+                attr,
+                "only named outputs can have attributes",
+            ));
+        } else {
+            None
+        };
+
+        Ok(Output {
+            pin,
+            typ: input.parse()?,
+        })
+    }
+}
+
+/// Attributes of a block, given as `name: value` to `ExecutionBlockHelper!`
+/// and as `name = value` to `#[block]`
+#[derive(Default)]
+pub struct Attributes {
+    id: Option<u32>,
+    name: Option<Ident>,
+    typ: Option<Ident>,
+    path: Option<syn::Path>,
+    next: Option<Vec<Ident>>,
+    pure: Option<bool>,
+    error_output: Option<syn::LitBool>,
+}
+
+impl Attributes {
+    /// Parses the value of the given attribute
+    pub fn parse_value(&mut self, attr: &Ident, input: ParseStream) -> Result<()> {
+        match attr.to_string().as_str() {
+            "id" => {
+                let lit: syn::LitInt = input.parse()?;
+                set(&mut self.id, attr, lit.base10_parse::<u32>()?)
+            }
+            "name" => set(&mut self.name, attr, input.parse()?),
+            "typ" => {
+                let value: Ident = input.parse()?;
+                if value == "Loop" {
+                    return Err(Error::new(
+                        value.span(),
+                        "loop blocks can't be created from a function, implement `bme::blocks::LoopBlock` instead",
+                    ));
+                }
+                if !BLOCK_TYPES.iter().any(|t| value == t) {
+                    return Err(Error::new(
+                        value.span(),
+                        format!(
+                            "unknown block type `{}`, expected one of {}",
+                            value,
+                            BLOCK_TYPES.join(", ")
+                        ),
+                    ));
+                }
+                set(&mut self.typ, attr, value)
+            }
+            "path" => set(
+                &mut self.path,
+                attr,
+                input.call(syn::Path::parse_mod_style)?,
+            ),
+            "next" => set(&mut self.next, attr, parse_names(input)?),
+            "pure" => {
+                let lit: syn::LitBool = input.parse()?;
+                set(&mut self.pure, attr, lit.value)
+            }
+            "error_output" => {
+                set(&mut self.error_output, attr, input.parse()?)
+            }
+            _ => Err(Error::new(
+                attr.span(),
+                format!(
+                    "unknown attribute `{}`, expected one of id, name, typ, path, next, pure, error_output",
+                    attr
+                ),
+            )),
+        }
+    }
+}
+
+impl Parse for Block {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attrs = Attributes::default();
+
+        // the attributes, each in the form `name: value,`
+        loop {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(Token![fn]) {
+                break;
+            } else if !lookahead.peek(Ident) {
+                return Err(lookahead.error());
+            }
+
+            let attr: Ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            attrs.parse_value(&attr, input)?;
+            input.parse::<Token![,]>()?;
+        }
+
+        // the block function `fn execute(a: A, ...) -> (B, ...) { ... }`
+        input.parse::<Token![fn]>()?;
+        let fn_name: Ident = input.parse()?;
+        if fn_name != "execute" {
+            return Err(Error::new(
+                fn_name.span(),
+                "the block function must be named `execute`",
+            ));
+        }
+
+        let content;
+        parenthesized!(content in input);
+        let inputs = Punctuated::<Input, Token![,]>::parse_terminated(&content)?;
+
+        input.parse::<Token![->]>()?;
+
+        // the outputs are either given as `(A, B)` or as `Result<(A, B)>`
+        let mut error = None;
+        let outputs = if input.peek(syn::token::Paren) {
+            parse_outputs(input)?
+        } else {
+            let result: Ident = input.parse()?;
+            if result != "Result" {
+                return Err(Error::new(
+                    result.span(),
+                    "expected the outputs in the form `(A, B)` or `Result<(A, B)>`",
+                ));
+            }
+
+            input.parse::<Token![<]>()?;
+            let outputs = if input.peek(syn::token::Paren) {
+                parse_outputs(input)?
+            } else {
+                vec![input.parse()?]
+            };
+
+            error = Some(if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            });
+            input.parse::<Token![>]>()?;
+
+            outputs
+        };
+
+        let code = input.parse()?;
+
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the block function"));
+        }
+
+        Block::new(
+            attrs,
+            None,
+            inputs.into_iter().collect(),
+            outputs,
+            error,
+            code,
+        )
+    }
+}
+
+impl Block {
+    /// Creates a block from its attributes, the name is required when no
+    /// default name is given
+    pub fn new(
+        attrs: Attributes,
+        default_name: Option<Ident>,
+        inputs: Vec<Input>,
+        outputs: Vec<Output>,
+        error: Option<Option<Type>>,
+        code: syn::Block,
+    ) -> Result<Block> {
+        let name = match attrs.name.or(default_name) {
+            Some(name) => name,
+            None => return Err(Error::new(Span::call_site(), "missing attribute `name`")),
+        };
+
+        let typ = attrs
+            .typ
+            .unwrap_or_else(|| Ident::new("Static", Span::call_site()));
+        let error_output = attrs.error_output.as_ref().is_some_and(|lit| lit.value);
+
+        // failures can only continue with an execution output of a normal block
+        if error_output && typ != "Normal" {
+            return Err(Error::new(
+                typ.span(),
+                "`error_output` is only available for blocks of the type Normal",
+            ));
+        }
+        if error_output && error.is_none() {
+            return Err(Error::new_spanned(
+                &attrs.error_output,
+                "`error_output` needs a block function which returns a `Result`",
+            ));
+        }
+
+        // a missing id has no token of its own, the error points at the name
+        let id = attrs
+            .id
+            .ok_or_else(|| Error::new(name.span(), "missing attribute `id`"))?;
+
+        Ok(Block {
+            id,
+            name,
+            typ,
+            path: attrs.path.unwrap_or_else(|| syn::parse_quote!(bme)),
+            next: attrs.next,
+            pure: attrs.pure.unwrap_or(true),
+            error_output,
+            description: None,
+            inputs,
+            outputs,
+            error,
+            code,
+        })
+    }
+
+    /// Sets the description of the block
+    pub fn with_description(mut self, description: Option<String>) -> Block {
+        self.description = description;
+        self
+    }
+
+    /// Generates the block struct and its `ExecutionBlock` implementation
+    pub fn expand(&self) -> Result<TokenStream> {
+        let Block {
+            id,
+            name,
+            typ,
+            path,
+            pure,
+            code,
+            ..
+        } = self;
+        let name_str = name.to_string();
+
+        let input_names = self
+            .inputs
+            .iter()
+            .map(|i| type_name(&i.typ))
+            .collect::<Result<Vec<_>>>()?;
+        let output_names = self
+            .outputs
+            .iter()
+            .map(|o| type_name(&o.typ))
+            .collect::<Result<Vec<_>>>()?;
+
+        // outputs of the type Next select the execution output and are no nodes,
+        // the message of a failure is given by an additional output
+        let mut output_nodes = output_names
+            .iter()
+            .filter(|o| *o != "Next")
+            .cloned()
+            .collect::<Vec<_>>();
+        let data_outputs = output_nodes.len();
+        if self.error_output {
+            output_nodes.push(String::from("String"));
+        }
+
+        let input_pins = self.inputs.iter().map(|i| i.pin.expand(path));
+
+        // outputs are either all named or not at all
+        let mut output_pins = vec![];
+        let mut unnamed = None;
+        for (o, n) in self.outputs.iter().zip(&output_names) {
+            match (&o.pin, n == "Next") {
+                (Some(_), true) => {
+                    return Err(Error::new_spanned(
+                        &o.typ,
+                        "the Next output selects the execution output and can't be named",
+                    ))
+                }
+                (Some(pin), false) => output_pins.push(pin.expand(path)),
+                (None, true) => {}
+                (None, false) => unnamed = unnamed.or(Some(&o.typ)),
+            }
+        }
+
+        if let (Some(typ), false) = (unnamed, output_pins.is_empty()) {
+            return Err(Error::new_spanned(
+                typ,
+                "expected a name for the output, either all outputs are named or none",
+            ));
+        }
+
+        // unnamed outputs get a pin without a name before the error message
+        if self.error_output {
+            let pin =
+                |name: &str| quote!(#path::Pin { name: #name, label: None, description: None });
+
+            if output_pins.is_empty() {
+                output_pins = (0..data_outputs).map(|_| pin("")).collect();
+            }
+            output_pins.push(pin("error"));
+        }
+
+        let output_pins_fn = if output_pins.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                fn get_output_pins(&self) -> &'static [#path::Pin] {
+                    &[#(#output_pins),*]
+                }
+            })
+        };
+
+        let description_fn = self.description.as_ref().map(|description| {
+            quote! {
+                fn get_description(&self) -> Option<&'static str> {
+                    Some(#description)
+                }
+            }
+        });
+
+        // the error output is added behind the other execution outputs
+        let mut next_names = match &self.next {
+            Some(next) => next.iter().map(|n| n.to_string()).collect(),
+            None if self.error_output => vec![String::from("Next")],
+            None => vec![],
+        };
+        let error_index = next_names.len();
+        if self.error_output {
+            next_names.push(String::from("Error"));
+        }
+
+        // the node ids of the execution outputs, the first one follows the
+        // run input and the others come after all data nodes
+        let data_nodes = self.inputs.len().max(output_nodes.len()) as u32;
+        let next_nodes = (0..next_names.len().max(1) as u32)
+            .map(|i| {
+                if i == 0 {
+                    1
+                } else {
+                    data_nodes * 2 + i * 2 + 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let next_fn = if next_names.is_empty() {
+            None
+        } else {
+            Some(quote! {
+                fn get_next_outputs(&self) -> &'static [&'static str] {
+                    &[#(#next_names),*]
+                }
+            })
+        };
+
+        // a failed block function either continues with the error output
+        // or fails the execution
+        let run = match &self.error {
+            None => quote! {
+                #[allow(unused_parens)]
+                let result = #code;
+            },
+            Some(error) => {
+                let error = match error {
+                    Some(error) => quote!(#error),
+                    None => quote!(#path::error::Error),
+                };
+                let failed = if self.error_output {
+                    let message_node = (data_outputs as u32 * 2) + 3;
+                    let error_node = next_nodes[error_index];
+
+                    quote! {
+                        Err(e) => {
+                            return Ok(vec![
+                                #path::Register {
+                                    block_id: _private_block_id,
+                                    node_id: #error_node,
+                                    value: Box::new(#path::types::Execution::new()),
+                                },
+                                #path::Register {
+                                    block_id: _private_block_id,
+                                    node_id: #message_node,
+                                    value: Box::new(e.to_string()),
+                                },
+                            ]);
+                        }
+                    }
+                } else {
+                    quote! {
+                        Err(e) => {
+                            use #path::error::convert::{ViaDisplay, ViaError, Wrap};
+                            return Err(Wrap(e).into_error().in_block(_private_block_id, #id));
+                        }
+                    }
+                };
+
+                quote! {
+                    #[allow(unused_parens)]
+                    let result = (|| -> ::std::result::Result<_, #error> #code)();
+                    let result = match result {
+                        Ok(value) => value,
+                        #failed
+                    };
+                }
+            }
+        };
+
+        let reads = self.inputs.iter().zip(&input_names).enumerate().map(|(i, (inp, typ_name))| {
+            let Input { pin, typ } = inp;
+            let name = &pin.name;
+            let node = (i as u32 * 2) + 2;
+
+            quote! {
+                let #name: #typ = match _private_input.get(#i) {
+                    Some(v) => v.as_ref().downcast_ref::<#typ>().ok_or_else(|| #path::error::Error::TypeMismatch {
+                        block_id: _private_block_id,
+                        node_id: #node,
+                        block_type_id: #id,
+                        expected: #typ_name.to_string(),
+                        found: v.get_name().to_string(),
+                    })?.clone(),
+                    None => return Err(#path::error::Error::MissingRegisterValue {
+                        block_id: _private_block_id,
+                        node_id: #node,
+                        block_type_id: #id,
+                    }),
+                };
+            }
+        });
+
+        let mut writes = vec![];
+        if typ != "Start" {
+            let mut node = 3u32;
+
+            for (i, o) in output_names.iter().enumerate() {
+                let value = if output_names.len() == 1 {
+                    quote!(result)
+                } else {
+                    let index = syn::Index::from(i);
+                    quote!(result.#index)
+                };
+
+                if o == "Next" {
+                    writes.push(quote! {
+                        out.push(#path::Register {
+                            block_id: _private_block_id,
+                            node_id: *[#(#next_nodes),*].get(#value.0).ok_or("The selected execution output is not available")?,
+                            value: Box::new(#path::types::Execution::new()),
+                        });
+                    });
+                } else {
+                    writes.push(quote! {
+                        out.push(#path::Register {
+                            block_id: _private_block_id,
+                            node_id: #node,
+                            value: Box::new(#value),
+                        });
+                    });
+                    node += 2;
+                }
+            }
+        }
+
+        Ok(quote! {
+            #[derive(Debug)]
+            pub struct #name {}
+
+            impl #path::ExecutionBlock for #name {
+                fn get_id(&self) -> u32 {
+                    #id
+                }
+
+                fn get_name(&self) -> &'static str {
+                    #name_str
+                }
+
+                fn get_type(&self) -> #path::ExecutionBlockType {
+                    #path::ExecutionBlockType::#typ
+                }
+
+                #description_fn
+
+                fn is_pure(&self) -> bool {
+                    #pure
+                }
+
+                fn get_inputs(&self) -> &'static [&'static str] {
+                    &[#(#input_names),*]
+                }
+
+                fn get_outputs(&self) -> &'static [&'static str] {
+                    &[#(#output_nodes),*]
+                }
+
+                fn get_input_pins(&self) -> &'static [#path::Pin] {
+                    &[#(#input_pins),*]
+                }
+
+                #output_pins_fn
+
+                #next_fn
+
+                fn execute(&self, input: Vec<#path::Register>, block_id: u32) -> #path::error::Result<Vec<#path::Register>> {
+                    let _private_input = input.into_iter().map(|r| r.value).collect::<Vec<Box<dyn #path::types::ExecutionType>>>();
+                    let _private_block_id = block_id;
+
+                    #(#reads)*
+
+                    // execute the block, a single output is given as `(value)`
+                    #run
+
+                    let mut out = vec![];
+
+                    #(#writes)*
+
+                    Ok(out)
+                }
+            }
+        })
+    }
+}
+
+/// Parses the outputs of the block function like `(a: A, B)`
+fn parse_outputs(input: ParseStream) -> Result<Vec<Output>> {
+    let content;
+    parenthesized!(content in input);
+    let outputs = Punctuated::<Output, Token![,]>::parse_terminated(&content)?;
+
+    Ok(outputs.into_iter().collect())
+}
+
+/// Parses a list of names like `[True, False]`
+pub fn parse_names(input: ParseStream) -> Result<Vec<Ident>> {
+    let content;
+    bracketed!(content in input);
+    let names = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+
+    Ok(names.into_iter().collect())
+}
+
+/// Sets an attribute, which is only allowed once
+pub fn set<T>(slot: &mut Option<T>, attr: &Ident, value: T) -> Result<()> {
+    if slot.is_some() {
+        return Err(Error::new(
+            attr.span(),
+            format!("duplicate attribute `{}`", attr),
+        ));
+    }
+
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Generates an `Option<&'static str>` from an optional string literal
+fn option(value: &Option<LitStr>) -> TokenStream {
+    match value {
+        Some(v) => quote!(Some(#v)),
+        None => quote!(None),
+    }
+}
+
+/// Name of the connection type for a type of an input or output.
+///
+/// This is the last segment of the type path, so `crate::blocks::list::List`
+/// is connected as `List` and `Vec<String>` as `Vec<String>`.
+pub fn type_name(typ: &Type) -> Result<String> {
+    match typ {
+        Type::Path(p) if p.qself.is_none() => {
+            let last = match p.path.segments.last() {
+                Some(s) => s,
+                None => return Err(Error::new_spanned(typ, "expected the path of a type")),
+            };
+
+            match &last.arguments {
+                PathArguments::None => Ok(last.ident.to_string()),
+                PathArguments::AngleBracketed(args) => {
+                    let args = args
+                        .args
+                        .iter()
+                        .map(|a| match a {
+                            GenericArgument::Type(t) => type_name(t),
+                            _ => Err(Error::new_spanned(a, "only type arguments are supported")),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+
+                    Ok(format!("{}<{}>", last.ident, args.join(", ")))
+                }
+                PathArguments::Parenthesized(_) => {
+                    Err(Error::new_spanned(typ, "function types are not supported"))
+                }
+            }
+        }
+        Type::Group(g) => type_name(&g.elem),
+        Type::Paren(p) => type_name(&p.elem),
+        _ => Err(Error::new_spanned(typ, "expected the path of a type")),
+    }
+}
@@ -0,0 +1,121 @@
+use crate::block::set;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, LitStr, Result};
+
+/// Generates the `ExecutionType` implementation for a type marked with
+/// `#[derive(ExecutionType)]`
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    if let Some(param) = input.generics.params.first() {
+        return Err(Error::new_spanned(
+            param,
+            "execution types can't be generic",
+        ));
+    }
+
+    let ident = &input.ident;
+    let mut name: Option<LitStr> = None;
+    let mut color: Option<LitStr> = None;
+    let mut edit_default: Option<LitStr> = None;
+    let mut value_check: Option<LitStr> = None;
+    let mut multi_output: Option<bool> = None;
+    let mut path: Option<syn::Path> = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("execution_type"))
+    {
+        attr.parse_nested_meta(|meta| match meta.path.get_ident() {
+            Some(i) if i == "name" => set(&mut name, i, meta.value()?.parse()?),
+            Some(i) if i == "color" => set(&mut color, i, meta.value()?.parse()?),
+            Some(i) if i == "edit_default" => set(&mut edit_default, i, meta.value()?.parse()?),
+            Some(i) if i == "value_check" => set(&mut value_check, i, meta.value()?.parse()?),
+            Some(i) if i == "multi_output" => set(&mut multi_output, i, true),
+            Some(i) if i == "path" => set(&mut path, i, meta.value()?.call(syn::Path::parse_mod_style)?),
+            _ => Err(meta.error(
+                "unknown attribute, expected one of name, color, edit_default, value_check, multi_output, path",
+            )),
+        })?;
+    }
+
+    let name = match name {
+        Some(name) => name.value(),
+        None => ident.to_string(),
+    };
+    let path = path.unwrap_or_else(|| syn::parse_quote!(bme));
+
+    let color_fn = color.map(|color| {
+        quote! {
+            fn get_color(&self) -> &'static str {
+                #color
+            }
+        }
+    });
+
+    let edit_default_fn = edit_default.map(|value| {
+        quote! {
+            fn get_edit_default(&self) -> Option<&'static str> {
+                Some(#value)
+            }
+        }
+    });
+
+    let value_check_fn = value_check.map(|value| {
+        quote! {
+            fn get_value_check(&self) -> Option<&'static str> {
+                Some(#value)
+            }
+        }
+    });
+
+    let multi_output_fn = multi_output.map(|value| {
+        quote! {
+            fn get_multi_output(&self) -> bool {
+                #value
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #path::types::ExecutionType for #ident {
+            fn get_name(&self) -> &'static str {
+                #name
+            }
+
+            fn from_json(&self, json: #path::serde_json::Value) -> #path::error::Result<Box<dyn #path::types::ExecutionType>> {
+                // values typed into the editor are given as json text
+                let value = match #path::serde_json::from_value::<#ident>(json.clone()) {
+                    Ok(value) => Ok(value),
+                    Err(e) => match &json {
+                        #path::serde_json::Value::String(s) => #path::serde_json::from_str::<#ident>(s).map_err(|_| e),
+                        _ => Err(e),
+                    },
+                };
+
+                match value {
+                    Ok(value) => Ok(Box::new(value)),
+                    Err(e) => Err(format!("{} is not a valid {}: {}", json, #name, e).into()),
+                }
+            }
+
+            fn to_json(&self) -> #path::serde_json::Value {
+                #path::serde_json::to_value(self).unwrap_or_else(|e| {
+                    panic!("The {} value can't be converted into json: {}", #name, e)
+                })
+            }
+
+            fn duplicate(&self) -> Box<dyn #path::types::ExecutionType> {
+                Box::new(::std::clone::Clone::clone(self))
+            }
+
+            #color_fn
+
+            #edit_default_fn
+
+            #value_check_fn
+
+            #multi_output_fn
+        }
+    })
+}
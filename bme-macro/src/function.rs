@@ -0,0 +1,209 @@
+use crate::block::{parse_names, set, type_name, Attributes, Block, Input, Output, Pin};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{ParseStream, Parser};
+use syn::{
+    Error, FnArg, GenericArgument, Ident, Pat, PathArguments, Result, ReturnType, Token, Type,
+};
+
+/// Generates the execution block for a function marked with `#[block]`.
+///
+/// The function itself is kept as it is, only the `#[pin]` attributes of
+/// its parameters are removed.
+pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let mut func: syn::ItemFn = syn::parse2(item)?;
+    let mut attrs = Attributes::default();
+    let mut output_names = None;
+
+    // the attributes in the form `name = value, ...`
+    let parser = |input: ParseStream| {
+        while !input.is_empty() {
+            let attr: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            if attr == "outputs" {
+                set(&mut output_names, &attr, parse_names(input)?)?;
+            } else {
+                attrs.parse_value(&attr, input)?;
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(())
+    };
+    parser.parse2(attr)?;
+
+    let sig = &mut func.sig;
+    if let Some(param) = sig.generics.params.first() {
+        return Err(Error::new_spanned(
+            param,
+            "block functions can't be generic",
+        ));
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "block functions can't be async",
+        ));
+    }
+
+    let mut inputs = vec![];
+    for arg in sig.inputs.iter_mut() {
+        let arg = match arg {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(r) => {
+                return Err(Error::new_spanned(r, "block functions can't take `self`"))
+            }
+        };
+
+        let name = match &*arg.pat {
+            Pat::Ident(p) if p.by_ref.is_none() && p.subpat.is_none() => p.ident.clone(),
+            pat => return Err(Error::new_spanned(pat, "expected the name of the input")),
+        };
+
+        // the pin attributes are only read by the macro
+        let (pins, others) = arg.attrs.drain(..).partition(|a| a.path().is_ident("pin"));
+        arg.attrs = others;
+
+        inputs.push(Input {
+            pin: Pin::new(name, &pins)?,
+            typ: (*arg.ty).clone(),
+        });
+    }
+
+    // the outputs can be wrapped into a `Result<T>` or `Result<T, E>`
+    let mut error = None;
+    let returned = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, typ) => match result_types(typ) {
+            Some((ok, err)) => {
+                error = Some(err.cloned());
+                Some(ok)
+            }
+            None => Some(&**typ),
+        },
+    };
+
+    // a tuple returns multiple outputs, any other type a single one
+    let types = match returned {
+        None => vec![],
+        Some(Type::Tuple(t)) => t.elems.iter().cloned().collect(),
+        Some(Type::Paren(p)) => vec![(*p.elem).clone()],
+        Some(typ) => vec![typ.clone()],
+    };
+
+    let mut outputs: Vec<Output> = types
+        .into_iter()
+        .map(|typ| Output { pin: None, typ })
+        .collect();
+
+    if let Some(names) = output_names {
+        let mut data_outputs = outputs
+            .iter_mut()
+            .filter(|o| type_name(&o.typ).ok().as_deref() != Some("Next"));
+
+        for name in names {
+            match data_outputs.next() {
+                Some(o) => o.pin = Some(Pin::new(name, &[])?),
+                None => {
+                    return Err(Error::new(
+                        name.span(),
+                        "the function has no output for this name",
+                    ))
+                }
+            }
+        }
+    }
+
+    // the generated block calls the function with its inputs
+    let fn_name = &sig.ident;
+    let args = inputs.iter().map(|i| &i.pin.name);
+    let code = syn::parse_quote!({ #fn_name(#(#args),*) });
+
+    let block = Block::new(
+        attrs,
+        Some(block_name(fn_name)),
+        inputs,
+        outputs,
+        error,
+        code,
+    )?
+    .with_description(description(&func.attrs));
+    let block = block.expand()?;
+
+    Ok(quote! {
+        #func
+
+        #block
+    })
+}
+
+/// The value and error type of a `Result<T>` or `Result<T, E>`
+fn result_types(typ: &Type) -> Option<(&Type, Option<&Type>)> {
+    let last = match typ {
+        Type::Path(p) if p.qself.is_none() => p.path.segments.last()?,
+        _ => return None,
+    };
+
+    let args = match &last.arguments {
+        PathArguments::AngleBracketed(args) if last.ident == "Result" => &args.args,
+        _ => return None,
+    };
+
+    let mut types = args.iter().map(|a| match a {
+        GenericArgument::Type(t) => Some(t),
+        _ => None,
+    });
+
+    match (types.next(), types.next(), types.next()) {
+        (Some(Some(ok)), None, None) => Some((ok, None)),
+        (Some(Some(ok)), Some(Some(err)), None) => Some((ok, Some(err))),
+        _ => None,
+    }
+}
+
+/// Name of the block struct for a function, `integer_add` becomes `IntegerAdd`
+fn block_name(fn_name: &Ident) -> Ident {
+    let name = fn_name
+        .to_string()
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+
+    Ident::new(&name, fn_name.span())
+}
+
+/// The doc comments of the function, which become the description of the block
+fn description(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect::<Vec<_>>();
+
+    let description = lines.join("\n").trim().to_string();
+    if description.is_empty() {
+        None
+    } else {
+        Some(description)
+    }
+}
@@ -1,304 +1,105 @@
 extern crate proc_macro;
+
+mod block;
+mod derive;
+mod function;
+
 use proc_macro::TokenStream;
 
+/// Creates an execution block from a function.
+///
+/// ```ignore
+/// ExecutionBlockHelper!(
+///     id: 64001,
+///     name: IntegerAdd,
+///     typ: Static,
+///
+///     fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+///         (inp1 + inp2)
+///     }
+/// );
+/// ```
+///
+/// The connection type of an input or output is the last segment of its
+/// type path. Optional attributes are `typ` (default `Static`), `path` to
+/// the `bme` crate (default `bme`), `next` for named execution outputs and
+/// `pure` (default `true`). Loop blocks can't be created with the macro,
+/// they implement `bme::blocks::LoopBlock`.
+///
+/// The parameter names become the names of the input pins. Outputs can be
+/// named the same way, like `-> (left: String, right: String)`. A pin takes
+/// an optional `#[pin(label = "Text", description = "...")]` attribute.
+///
+/// A block which can fail returns its outputs as `-> Result<(A, B)>` or
+/// `-> Result<(A, B), E>`. An `Err` fails the execution with an error tagged
+/// with the block, or continues with an additional execution output named
+/// `Error` for normal blocks with `error_output: true`. The message of the
+/// error is then given by an additional `String` output named `error`, the
+/// other outputs of the block are left unset. The error is either
+/// the `Error` of the `bme` crate, or any type implementing `Display`, which
+/// becomes an `Error::Message`.
 #[proc_macro]
 #[allow(non_snake_case)]
 pub fn ExecutionBlockHelper(item: TokenStream) -> TokenStream {
-    // Optional debug printout
-    /*for i in item.clone().into_iter() {
-        println!("{:?}", i);
-    }*/
-
-    let mut stream = item.into_iter();
-    let mut id = String::from("");
-    let mut name = String::from("");
-    let mut typ = String::from("Static");
-    let mut path = String::from("bme");
-    let mut inputs: Vec<(String, String)> = vec![];
-    let mut outputs: Vec<String> = vec![];
-    let mut code: proc_macro::TokenStream = "{ }".parse().unwrap();
+    let block = syn::parse_macro_input!(item as block::Block);
 
-    loop {
-        match stream.next() {
-            None => {
-                break;
-            }
-            Some(token) => {
-                let attr = get_ident(&token).expect("No ident given").to_string();
-
-                match attr.as_str() {
-                    "id" => {
-                        id = get_id(&mut stream);
-                    }
-                    "name" => {
-                        name = get_name(&mut stream);
-                    }
-                    "typ" => {
-                        typ = get_typ(&mut stream);
-                    }
-                    "path" => {
-                        path = get_path(&mut stream);
-                    }
-                    "fn" => {
-                        get_execute(&mut stream);
-                        inputs = get_inputs(&mut stream);
-                        get_sign(&mut stream);
-                        outputs = get_outputs(&mut stream);
-                        code = get_code(&mut stream);
-                    }
-                    _ => {
-                        break;
-                    }
-                }
-            }
-        }
+    match block.expand() {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
     }
-
-    // debug printout
-    /*println!("id: {}", id);
-    println!("name: {}", name);
-    println!("description: {}", description);
-    println!("path: {}", path);
-    println!("inputs: {:?}", inputs);
-    println!("outputs: {:?}", outputs);
-    println!("code: {:?}", code);*/
-
-    let mut inp_str = String::from("");
-    for s in &inputs {
-        inp_str.push_str(&format!("\"{}\",", s.1));
-    }
-
-    let mut out_str = String::from("");
-    for o in &outputs {
-        out_str.push_str(&format!("\"{}\",", o));
-    }
-
-    let mut fn_inp_str = String::from("");
-    for (i, s) in inputs.iter().enumerate() {
-        fn_inp_str.push_str(&format!(
-            "let {} : {} = _private_input.get({}).unwrap().as_ref().downcast_ref::<{}>().unwrap().clone(); \n",
-            s.0,
-            s.1,
-            i,
-            s.1
-        ));
-    }
-
-    let mut fn_out_str = String::from("");
-    if typ != "Start" {
-        if outputs.len() == 1 {
-            fn_out_str.push_str(&format!(
-                "out.push({}::Register{{ block_id: _private_block_id, node_id: {}, value: Box::new(result) }});",
-                path,
-                3,
-            ));
-        } else {
-            for (i, s) in outputs.iter().enumerate() {
-                fn_out_str.push_str(&format!(
-                "out.push({}::Register{{ block_id: _private_block_id, node_id: {}, value: Box::new(result.{}) }});",
-                path,
-                (i * 2) + 3,
-                i
-            ));
-            }
-        }
-    }
-
-    let c = format!(r#"
-        #[derive(Debug)]
-        pub struct {name} {{}}
-        impl {path}::ExecutionBlock for {name} {{
-            fn get_id(&self) -> u32 {{
-                {id}
-            }}
-
-            fn get_name(&self) -> &'static str {{
-                "{name}"
-            }}
-
-            fn get_type(&self) -> {path}::ExecutionBlockType {{
-                {path}::ExecutionBlockType::{typ}
-            }}
-
-            fn get_inputs(&self) -> &'static [&'static str] {{
-                &[{inp_str}]
-            }}
-
-            fn get_outputs(&self) -> &'static [&'static str] {{
-                &[{out_str}]
-            }}
-
-            fn execute(&self, input: Vec<{path}::Register>, block_id: u32) -> {path}::error::Result<Vec<{path}::Register>> {{
-                let _private_input = input.into_iter().map(|r| r.value).collect::<Vec<Box<{path}::types::ExecutionType>>>();
-                let _private_block_id = block_id;
-
-                {fn_inp_str}
-
-                // execute the block
-                let result = {{ {code} }};
-
-
-                let mut out = vec!();
-
-                {fn_out_str}
-
-                Ok(out)
-            }}
-        }}
-    "#, name=name, id=id, typ=typ, inp_str=inp_str, out_str=out_str, fn_inp_str=fn_inp_str, fn_out_str=fn_out_str, code=code.to_string(), path=path);
-
-    // debug printout
-    //println!("{}", c);
-
-    c.parse().unwrap()
 }
 
-fn get_ident(inp: &proc_macro::TokenTree) -> Option<proc_macro::Ident> {
-    match inp {
-        proc_macro::TokenTree::Ident(i) => Some(i.clone()),
-        _ => None,
-    }
-}
-
-fn get_literal(inp: &proc_macro::TokenTree) -> Option<proc_macro::Literal> {
-    match inp {
-        proc_macro::TokenTree::Literal(i) => Some(i.clone()),
-        _ => None,
-    }
-}
-
-fn get_punct(inp: &proc_macro::TokenTree) -> Option<proc_macro::Punct> {
-    match inp {
-        proc_macro::TokenTree::Punct(i) => Some(i.clone()),
-        _ => None,
-    }
-}
-
-fn get_group(inp: &proc_macro::TokenTree) -> Option<proc_macro::Group> {
-    match inp {
-        proc_macro::TokenTree::Group(i) => Some(i.clone()),
-        _ => None,
-    }
-}
-
-fn get_id(stream: &mut proc_macro::token_stream::IntoIter) -> String {
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    let res = get_literal(&stream.next().expect("No id given"))
-        .expect("No id given")
-        .to_string();
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    res
-}
-
-fn get_name(stream: &mut proc_macro::token_stream::IntoIter) -> String {
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    let res = get_ident(&stream.next().expect("No name given"))
-        .expect("No name given")
-        .to_string();
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    res
-}
-
-fn get_typ(stream: &mut proc_macro::token_stream::IntoIter) -> String {
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    let res = get_ident(&stream.next().expect("No name given"))
-        .expect("No name given")
-        .to_string();
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    res
-}
-
-fn get_path(stream: &mut proc_macro::token_stream::IntoIter) -> String {
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    let res = get_ident(&stream.next().expect("No path given"))
-        .expect("No path given")
-        .to_string();
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    res
-}
-
-fn get_execute(stream: &mut proc_macro::token_stream::IntoIter) {
-    // get function name execute
-    if get_ident(&stream.next().expect("No function name found"))
-        .expect("No function name found")
-        .to_string()
-        != "execute"
-    {
-        panic!("No function name execution found");
-    }
-}
-
-fn get_inputs(stream: &mut proc_macro::token_stream::IntoIter) -> Vec<(String, String)> {
-    let mut inputs = vec![];
-
-    let mut attr_stream = get_group(&stream.next().expect("Function inputs defined"))
-        .expect("Function inputs defined")
-        .stream()
-        .into_iter();
-    loop {
-        match attr_stream.next() {
-            None => {
-                break;
-            }
-            Some(a) => {
-                // get the first attribute
-                let mut attr = a.to_string();
-
-                // when the first attribute is a seperator try again
-                if attr == "," {
-                    attr = attr_stream
-                        .next()
-                        .expect("No Value for attribute")
-                        .to_string();
-                }
-
-                // get the seperator
-                get_punct(&attr_stream.next().expect("No seperator found"))
-                    .expect("No seperator found");
-
-                // get the value
-                let value = attr_stream
-                    .next()
-                    .expect("No Value for attribute")
-                    .to_string();
-
-                // push name and value
-                inputs.push((attr, value));
-            }
+/// Creates an execution block from an ordinary function.
+///
+/// ```ignore
+/// /// Adds two integers
+/// #[bme::block(id = 64001, typ = Static)]
+/// fn integer_add(inp1: Integer, inp2: Integer) -> Integer {
+///     inp1 + inp2
+/// }
+/// ```
+///
+/// Takes the same attributes as `ExecutionBlockHelper!`, the `name` of the
+/// block defaults to the function name in camel case, like `IntegerAdd`.
+/// Outputs are named with `outputs = [left, right]` and a function returning
+/// a `Result` can fail like described there. The doc comments become
+/// the description of the block and the function stays callable as it is.
+#[proc_macro_attribute]
+pub fn block(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match function::expand(attr.into(), item.clone().into()) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => {
+            let mut tokens: TokenStream = e.to_compile_error().into();
+            tokens.extend(item);
+            tokens
         }
     }
-
-    inputs
 }
 
-fn get_outputs(stream: &mut proc_macro::token_stream::IntoIter) -> Vec<String> {
-    let mut outputs = vec![];
-
-    // get the inputs of the function
-    for i in get_group(&stream.next().expect("Function inputs not defined"))
-        .expect("Function inputs not defined")
-        .stream()
-        .into_iter()
-    {
-        let attr = i.to_string();
-        if attr == "," {
-            continue;
-        }
-        outputs.push(attr);
+/// Implements `ExecutionType` for a type which implements `Clone` and the
+/// serde traits.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, Serialize, Deserialize, ExecutionType)]
+/// #[execution_type(color = "orange", edit_default = "{}", multi_output)]
+/// pub struct Customer {
+///     name: String,
+/// }
+/// ```
+///
+/// The values are converted from and into json with serde, the `edit_default`
+/// is the value the editor shows for a new input. Further optional attributes
+/// are the `name` of the connection type, which defaults to the type name,
+/// a `value_check` regex and the `path` to the `bme` crate.
+///
+/// `to_json` panics with the name of the type when serde can't convert the
+/// value into json, like a map with keys which are no strings.
+#[proc_macro_derive(ExecutionType, attributes(execution_type))]
+pub fn derive_execution_type(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+
+    match derive::expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
     }
-
-    outputs
-}
-
-fn get_sign(stream: &mut proc_macro::token_stream::IntoIter) {
-    // get -> sign
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-    get_punct(&stream.next().expect("No seperator found")).expect("No seperator found");
-}
-
-fn get_code(stream: &mut proc_macro::token_stream::IntoIter) -> proc_macro::TokenStream {
-    // get the code
-    get_group(&stream.next().expect("Function code not defined"))
-        .expect("Function code not defined")
-        .stream()
 }
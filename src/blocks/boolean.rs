@@ -0,0 +1,155 @@
+use crate::error::Result;
+use bme_macro::ExecutionBlockHelper;
+
+pub type Boolean = bool;
+
+impl crate::types::ExecutionType for Boolean {
+    fn get_name(&self) -> &'static str {
+        "Boolean"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn crate::types::ExecutionType>> {
+        use std::str::FromStr;
+
+        if let Ok(s) = serde_json::from_value::<Boolean>(json.clone()) {
+            return Ok(Box::new(s));
+        }
+
+        if let Ok(s) = serde_json::from_value::<String>(json.clone()) {
+            if let Ok(f) = Boolean::from_str(s.trim()) {
+                return Ok(Box::new(f));
+            }
+        }
+
+        Err(format!("{} is not a valid Boolean", json).into())
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
+        Box::new(*self)
+    }
+
+    fn get_color(&self) -> &'static str {
+        "red"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("false")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(r"^\s*(true|false)\s*$")
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(BooleanAnd {}))?;
+    logic.add_block(Box::new(BooleanOr {}))?;
+    logic.add_block(Box::new(BooleanNot {}))?;
+    logic.add_block(Box::new(BooleanXor {}))?;
+
+    Ok(())
+}
+
+ExecutionBlockHelper!(
+    id: 61001,
+    name: BooleanAnd,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Boolean, inp2: Boolean) -> (Boolean) {
+        (inp1 && inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 61002,
+    name: BooleanOr,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Boolean, inp2: Boolean) -> (Boolean) {
+        (inp1 || inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 61003,
+    name: BooleanNot,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Boolean) -> (Boolean) {
+        (!inp1)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 61004,
+    name: BooleanXor,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Boolean, inp2: Boolean) -> (Boolean) {
+        (inp1 ^ inp2)
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::execute_block;
+    use crate::types::ExecutionType;
+    use crate::{ExecutionBlock, Logic};
+    use serde_json::json;
+
+    #[test]
+    fn logic_blocks() {
+        let table = [(false, false), (false, true), (true, false), (true, true)];
+
+        for &(a, b) in &table {
+            let run = |block: &dyn ExecutionBlock| {
+                execute_block(block, vec![Box::new(a), Box::new(b)]).unwrap()
+            };
+
+            assert_eq!(run(&BooleanAnd {}), vec![json!(a && b)]);
+            assert_eq!(run(&BooleanOr {}), vec![json!(a || b)]);
+            assert_eq!(run(&BooleanXor {}), vec![json!(a ^ b)]);
+        }
+
+        assert_eq!(
+            execute_block(&BooleanNot {}, vec![Box::new(true)]).unwrap(),
+            vec![json!(false)]
+        );
+    }
+
+    #[test]
+    fn comparison_blocks() {
+        let logic = Logic::default();
+        let compare = |id, a: i64, b: i64| {
+            execute_block(logic.get_block(id).unwrap(), vec![Box::new(a), Box::new(b)]).unwrap()
+        };
+
+        assert_eq!(compare(64_006, 2, 2), vec![json!(true)]);
+        assert_eq!(compare(64_007, 2, 2), vec![json!(false)]);
+        assert_eq!(compare(64_008, 2, 3), vec![json!(true)]);
+        assert_eq!(compare(64_009, 2, 3), vec![json!(false)]);
+    }
+
+    #[test]
+    fn booleans_from_json() {
+        let value = |json| false.from_json(json).map(|v| v.to_json());
+
+        assert_eq!(value(json!(true)).unwrap(), json!(true));
+        assert_eq!(value(json!(" false ")).unwrap(), json!(false));
+        assert!(value(json!("yes")).is_err());
+        assert!(value(json!(1)).is_err());
+    }
+}
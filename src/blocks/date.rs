@@ -0,0 +1,1066 @@
+use crate::blocks::integer::Integer;
+use crate::error::{Error, Result};
+use crate::types::ExecutionType;
+use crate::{ExecutionBlock, ExecutionBlockType, Register};
+use bme_macro::ExecutionBlockHelper;
+use std::sync::Arc;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// Dates are limited to around 270 thousand years around 1970
+const MAX_DAYS: i64 = 100_000_000;
+
+/// Calendar date, as days since 1970-01-01
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Date {
+    pub days: i64,
+}
+
+/// Point in time in UTC, as milliseconds since 1970-01-01T00:00:00Z
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct DateTime {
+    pub millis: i64,
+}
+
+/// Exact time span in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Duration {
+    pub millis: i64,
+}
+
+/// Source of the current time for the `Now` and `Today` blocks
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    fn now(&self) -> Result<DateTime>;
+}
+
+/// Clock which returns the time of the system
+#[derive(Debug, Default)]
+pub struct SystemClock {}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Result<DateTime> {
+        if cfg!(target_arch = "wasm32") {
+            Ok(DateTime::new(js_sys::Date::now() as i64))
+        } else {
+            let since = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+            Ok(DateTime::new(since.as_millis() as i64))
+        }
+    }
+}
+
+/// Clock which always returns the same time, for tests and replays
+#[derive(Debug)]
+pub struct FixedClock(pub DateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> Result<DateTime> {
+        Ok(self.0)
+    }
+}
+
+/// Replaces the clock used by the `Now` and `Today` blocks of the logic
+pub fn set_clock(logic: &mut crate::Logic, clock: Arc<dyn Clock>) -> Result<()> {
+    logic.remove_block(71001);
+    logic.remove_block(71002);
+    logic.add_block(Box::new(Now {
+        clock: clock.clone(),
+    }))?;
+    logic.add_block(Box::new(Today { clock }))?;
+
+    Ok(())
+}
+
+fn out_of_range() -> Error {
+    "The date is out of range".into()
+}
+
+/// Returns the days since 1970-01-01 for the given date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Returns the year, month and day for the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses a number with exactly the given amount of digits
+fn parse_digits(s: &str, digits: usize) -> Option<i64> {
+    if s.len() != digits || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    s.parse().ok()
+}
+
+/// Parses a year of four digits, or of up to six digits when it has a sign
+fn parse_year(s: &str, signed: bool) -> Option<i64> {
+    match s.len() {
+        4 => parse_digits(s, 4),
+        5 | 6 if signed => parse_digits(s, s.len()),
+        _ => None,
+    }
+}
+
+impl Date {
+    pub fn new(days: i64) -> Date {
+        Date { days }
+    }
+
+    /// Creates a date, when the day exists within the calendar
+    pub fn from_ymd(year: i64, month: i64, day: i64) -> Result<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(format!("{:04}-{:02}-{:02} is not a valid Date", year, month, day).into());
+        }
+
+        // a rough bound on the year keeps the calculation from overflowing,
+        // the exact range is checked on the days
+        match year.abs() <= MAX_DAYS / 300 {
+            true => Date::new(0).add_days(days_from_civil(year, month, day)),
+            false => Err(out_of_range()),
+        }
+    }
+
+    /// Parses an ISO-8601 date like `2020-02-29`, years outside of 0000 to
+    /// 9999 are given with a sign and more digits like `+10000-01-01`
+    pub fn parse(s: &str) -> Result<Date> {
+        let err = || Error::from(format!("{} is not a valid Date", s));
+        let s = s.trim();
+
+        let (sign, rest) = match s.strip_prefix('-') {
+            Some(r) => (-1, r),
+            None => (1, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let parts = rest.split('-').collect::<Vec<_>>();
+
+        match parts.as_slice() {
+            [y, m, d] => Date::from_ymd(
+                sign * parse_year(y, rest.len() < s.len()).ok_or_else(err)?,
+                parse_digits(m, 2).ok_or_else(err)?,
+                parse_digits(d, 2).ok_or_else(err)?,
+            )
+            .map_err(|_| err()),
+            _ => Err(err()),
+        }
+    }
+
+    pub fn year_month_day(&self) -> (i64, i64, i64) {
+        civil_from_days(self.days)
+    }
+
+    /// Returns the ISO day of the week, from 1 for monday to 7 for sunday
+    pub fn day_of_week(&self) -> i64 {
+        // 1970-01-01 was a thursday
+        (self.days + 3).rem_euclid(7) + 1
+    }
+
+    pub fn is_business_day(&self) -> bool {
+        self.day_of_week() <= 5
+    }
+
+    pub fn add_days(&self, days: i64) -> Result<Date> {
+        match self.days.checked_add(days) {
+            Some(d) if d.abs() <= MAX_DAYS => Ok(Date::new(d)),
+            _ => Err(out_of_range()),
+        }
+    }
+
+    /// Returns the start of the day in UTC
+    pub fn to_date_time(&self) -> Result<DateTime> {
+        let millis = self.days.checked_mul(MILLIS_PER_DAY);
+        Ok(DateTime::new(millis.ok_or_else(out_of_range)?))
+    }
+
+    pub fn start_of_month(&self) -> Date {
+        let (year, month, _) = self.year_month_day();
+        Date::new(days_from_civil(year, month, 1))
+    }
+
+    /// Adds the given number of business days (monday to friday)
+    pub fn add_business_days(&self, days: i64) -> Result<Date> {
+        if days == 0 {
+            return Ok(*self);
+        }
+
+        let step = days.signum();
+
+        // move from a weekend to the last business day before the counting direction
+        let mut date = *self;
+        while !date.is_business_day() {
+            date = date.add_days(-step)?;
+        }
+
+        // full weeks keep the day of the week
+        let weeks = days / 5;
+        date = date.add_days(weeks.checked_mul(7).ok_or_else(out_of_range)?)?;
+
+        for _ in 0..(days % 5).abs() {
+            date = date.add_days(step)?;
+            while !date.is_business_day() {
+                date = date.add_days(step)?;
+            }
+        }
+
+        Ok(date)
+    }
+
+    /// Counts the business days from this date (included) to the other date (excluded)
+    pub fn business_days_until(&self, other: &Date) -> i64 {
+        if other.days < self.days {
+            return -other.business_days_until(self);
+        }
+
+        let days = other.days - self.days;
+        let weeks = days / 7;
+        let rest = (0..days % 7)
+            .filter(|i| Date::new(self.days + weeks * 7 + i).is_business_day())
+            .count() as i64;
+
+        weeks * 5 + rest
+    }
+
+    /// Counts the full years from this date to the other date, like for an age
+    pub fn years_until(&self, other: &Date) -> i64 {
+        if other.days < self.days {
+            return -other.years_until(self);
+        }
+
+        let (y1, m1, d1) = self.year_month_day();
+        let (y2, m2, d2) = other.year_month_day();
+
+        if (m2, d2) < (m1, d1) {
+            y2 - y1 - 1
+        } else {
+            y2 - y1
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (y, m, d) = self.year_month_day();
+
+        if (0..=9999).contains(&y) {
+            write!(f, "{:04}-{:02}-{:02}", y, m, d)
+        } else {
+            write!(f, "{:+05}-{:02}-{:02}", y, m, d)
+        }
+    }
+}
+
+impl DateTime {
+    pub fn new(millis: i64) -> DateTime {
+        DateTime { millis }
+    }
+
+    /// Parses an ISO-8601 date time like `2020-02-29T13:45:00.250+01:00`,
+    /// without a time zone the time is taken as UTC
+    pub fn parse(s: &str) -> Result<DateTime> {
+        let err = || Error::from(format!("{} is not a valid DateTime", s));
+        let s = s.trim();
+
+        // the date has more than ten characters when the year has a sign
+        let end = s.find(['T', ' ']).unwrap_or(s.len());
+        let date = Date::parse(&s[..end]).map_err(|_| err())?;
+        let rest = &s[end..];
+
+        if rest.is_empty() {
+            return date.to_date_time();
+        }
+
+        let rest = rest
+            .strip_prefix('T')
+            .or_else(|| rest.strip_prefix(' '))
+            .ok_or_else(err)?;
+
+        // split the time zone from the time
+        let (time, offset) = match rest.find(['Z', '+', '-']) {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+
+        let offset = match offset {
+            "" | "Z" => 0,
+            o => {
+                let sign = if o.starts_with('-') { -1 } else { 1 };
+                let parts = o[1..].split(':').collect::<Vec<_>>();
+                let (h, m) = match parts.as_slice() {
+                    [h, m] => (parse_digits(h, 2), parse_digits(m, 2)),
+                    [hm] if hm.len() == 4 => (parse_digits(&hm[..2], 2), parse_digits(&hm[2..], 2)),
+                    _ => (None, None),
+                };
+
+                sign * (h.ok_or_else(err)? * 60 + m.ok_or_else(err)?) * 60_000
+            }
+        };
+
+        let (time, fraction) = match time.find('.') {
+            Some(i) => (&time[..i], &time[i + 1..]),
+            None => (time, ""),
+        };
+
+        let parts = time.split(':').collect::<Vec<_>>();
+        let (h, m, sec) = match parts.as_slice() {
+            [h, m] => (parse_digits(h, 2), parse_digits(m, 2), Some(0)),
+            [h, m, sec] => (parse_digits(h, 2), parse_digits(m, 2), parse_digits(sec, 2)),
+            _ => (None, None, None),
+        };
+        let (h, m, sec) = (h.ok_or_else(err)?, m.ok_or_else(err)?, sec.ok_or_else(err)?);
+
+        if h > 23 || m > 59 || sec > 59 {
+            return Err(err());
+        }
+
+        let millis = match fraction {
+            "" => 0,
+            f if f.len() <= 9 => {
+                let f = format!("{:0<3}", f);
+                parse_digits(&f[..3], 3).ok_or_else(err)?
+            }
+            _ => return Err(err()),
+        };
+
+        Ok(DateTime::new(
+            date.days * MILLIS_PER_DAY + ((h * 60 + m) * 60 + sec) * 1000 + millis - offset,
+        ))
+    }
+
+    pub fn date(&self) -> Date {
+        Date::new(self.millis.div_euclid(MILLIS_PER_DAY))
+    }
+
+    /// Returns the hour, minute, second and millisecond of the day
+    pub fn time(&self) -> (i64, i64, i64, i64) {
+        let ms = self.millis.rem_euclid(MILLIS_PER_DAY);
+        (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+    }
+
+    pub fn add(&self, duration: &Duration) -> Result<DateTime> {
+        let millis = self.millis.checked_add(duration.millis);
+        Ok(DateTime::new(millis.ok_or_else(out_of_range)?))
+    }
+
+    /// Returns the duration from this time to the other time
+    pub fn until(&self, other: &DateTime) -> Result<Duration> {
+        let millis = other.millis.checked_sub(self.millis);
+        Ok(Duration::new(millis.ok_or_else(out_of_range)?))
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (h, m, s, ms) = self.time();
+        write!(f, "{}T{:02}:{:02}:{:02}", self.date(), h, m, s)?;
+
+        if ms != 0 {
+            write!(f, ".{:03}", ms)?;
+        }
+
+        write!(f, "Z")
+    }
+}
+
+impl Duration {
+    pub fn new(millis: i64) -> Duration {
+        Duration { millis }
+    }
+
+    /// Parses an ISO-8601 duration like `P1DT2H30M` or `-PT0.5S`. Years and
+    /// months are not supported, because their length is not fixed.
+    pub fn parse(s: &str) -> Result<Duration> {
+        let err = || Error::from(format!("{} is not a valid Duration", s));
+        let s = s.trim();
+
+        let (sign, rest) = match s.strip_prefix('-') {
+            Some(r) => (-1, r),
+            None => (1, s),
+        };
+
+        let rest = rest.strip_prefix('P').ok_or_else(err)?;
+        let mut millis: i64 = 0;
+        let mut number = String::new();
+        let mut time = false;
+        let mut parts = 0;
+        let mut time_parts = 0;
+
+        for c in rest.chars() {
+            let unit = match (c, time) {
+                ('T', false) if number.is_empty() => {
+                    time = true;
+                    continue;
+                }
+                (c, _) if c.is_ascii_digit() || c == '.' => {
+                    number.push(c);
+                    continue;
+                }
+                ('W', false) => 7 * MILLIS_PER_DAY,
+                ('D', false) => MILLIS_PER_DAY,
+                ('H', true) => 3_600_000,
+                ('M', true) => 60_000,
+                ('S', true) => 1000,
+                ('Y', false) | ('M', false) => {
+                    return Err(format!(
+                        "{} is not a valid Duration, years and months are not supported",
+                        s
+                    )
+                    .into())
+                }
+                _ => return Err(err()),
+            };
+
+            let value = number.parse::<f64>().map_err(|_| err())?;
+            let part = (value * unit as f64).round();
+
+            if !part.is_finite() || part.abs() > i64::MAX as f64 {
+                return Err(err());
+            }
+
+            millis = millis.checked_add(part as i64).ok_or_else(err)?;
+            number.clear();
+            parts += 1;
+            time_parts += time as usize;
+        }
+
+        if parts == 0 || (time && time_parts == 0) || !number.is_empty() {
+            return Err(err());
+        }
+
+        Ok(Duration::new(sign * millis))
+    }
+
+    pub fn from_parts(days: i64, hours: i64, minutes: i64, seconds: i64) -> Result<Duration> {
+        let millis = [
+            (days, MILLIS_PER_DAY),
+            (hours, 3_600_000),
+            (minutes, 60_000),
+            (seconds, 1000),
+        ]
+        .iter()
+        .try_fold(0i64, |sum, (v, unit)| {
+            sum.checked_add(v.checked_mul(*unit)?)
+        });
+
+        Ok(Duration::new(millis.ok_or_else(out_of_range)?))
+    }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.millis == 0 {
+            return write!(f, "PT0S");
+        }
+
+        if self.millis < 0 {
+            write!(f, "-")?;
+        }
+
+        let ms = self.millis.unsigned_abs();
+        let day = MILLIS_PER_DAY as u64;
+        let (days, h, m, s, ms) = (
+            ms / day,
+            ms % day / 3_600_000,
+            ms / 60_000 % 60,
+            ms / 1000 % 60,
+            ms % 1000,
+        );
+
+        write!(f, "P")?;
+
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+
+        if h > 0 || m > 0 || s > 0 || ms > 0 {
+            write!(f, "T")?;
+        }
+
+        if h > 0 {
+            write!(f, "{}H", h)?;
+        }
+
+        if m > 0 {
+            write!(f, "{}M", m)?;
+        }
+
+        match (s, ms) {
+            (0, 0) => Ok(()),
+            (s, 0) => write!(f, "{}S", s),
+            (s, ms) => write!(f, "{}.{}S", s, format!("{:03}", ms).trim_end_matches('0')),
+        }
+    }
+}
+
+/// Formats a date time with a pattern, where `YYYY`, `MM`, `DD`, `HH`, `mm`,
+/// `ss` and `SSS` are replaced by the year, month, day, hour, minute, second
+/// and millisecond
+pub fn format(time: &DateTime, pattern: &str) -> String {
+    let (year, month, day) = time.date().year_month_day();
+    let (h, m, s, ms) = time.time();
+
+    let tokens = [
+        ("YYYY", format!("{:04}", year)),
+        ("SSS", format!("{:03}", ms)),
+        ("MM", format!("{:02}", month)),
+        ("DD", format!("{:02}", day)),
+        ("HH", format!("{:02}", h)),
+        ("mm", format!("{:02}", m)),
+        ("ss", format!("{:02}", s)),
+    ];
+
+    let mut result = String::new();
+    let mut rest = pattern;
+
+    'outer: while let Some(c) = rest.chars().next() {
+        for (token, value) in &tokens {
+            if let Some(r) = rest.strip_prefix(token) {
+                result.push_str(value);
+                rest = r;
+                continue 'outer;
+            }
+        }
+
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    result
+}
+
+impl ExecutionType for Date {
+    fn get_name(&self) -> &'static str {
+        "Date"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+        match json {
+            serde_json::Value::String(s) => Ok(Box::new(Date::parse(&s)?)),
+            j => Err(format!("{} is not a valid Date", j).into()),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.to_string())
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
+        Box::new(*self)
+    }
+
+    fn get_color(&self) -> &'static str {
+        "purple"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("1970-01-01")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(r"^\s*([0-9]{4}|[+-][0-9]{4,6})-[0-9]{2}-[0-9]{2}\s*$")
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+impl ExecutionType for DateTime {
+    fn get_name(&self) -> &'static str {
+        "DateTime"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+        match json {
+            serde_json::Value::String(s) => Ok(Box::new(DateTime::parse(&s)?)),
+            j => Err(format!("{} is not a valid DateTime", j).into()),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.to_string())
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
+        Box::new(*self)
+    }
+
+    fn get_color(&self) -> &'static str {
+        "violet"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("1970-01-01T00:00:00Z")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(
+            r"^\s*([0-9]{4}|[+-][0-9]{4,6})-[0-9]{2}-[0-9]{2}([T ][0-9]{2}:[0-9]{2}(:[0-9]{2}(\.[0-9]+)?)?(Z|[+-][0-9]{2}:?[0-9]{2})?)?\s*$",
+        )
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+impl ExecutionType for Duration {
+    fn get_name(&self) -> &'static str {
+        "Duration"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+        match json {
+            serde_json::Value::String(s) => Ok(Box::new(Duration::parse(&s)?)),
+            j => Err(format!("{} is not a valid Duration", j).into()),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.to_string())
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
+        Box::new(*self)
+    }
+
+    fn get_color(&self) -> &'static str {
+        "plum"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("PT0S")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(r"^\s*-?P([0-9.]+W)?([0-9.]+D)?(T([0-9.]+H)?([0-9.]+M)?([0-9.]+S)?)?\s*$")
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    set_clock(logic, Arc::new(SystemClock::default()))?;
+    logic.add_block(Box::new(DateCreate {}))?;
+    logic.add_block(Box::new(DateAddDays {}))?;
+    logic.add_block(Box::new(DateTimeAddDuration {}))?;
+    logic.add_block(Box::new(DateDifference {}))?;
+    logic.add_block(Box::new(DateTimeDifference {}))?;
+    logic.add_block(Box::new(DateDayOfWeek {}))?;
+    logic.add_block(Box::new(DateStartOfMonth {}))?;
+    logic.add_block(Box::new(DateAddBusinessDays {}))?;
+    logic.add_block(Box::new(DateBusinessDaysBetween {}))?;
+    logic.add_block(Box::new(DateYearsBetween {}))?;
+    logic.add_block(Box::new(DateFormat {}))?;
+    logic.add_block(Box::new(DateTimeFormat {}))?;
+    logic.add_block(Box::new(DateTimeToDate {}))?;
+    logic.add_block(Box::new(DateToDateTime {}))?;
+    logic.add_block(Box::new(DurationCreate {}))?;
+    logic.add_block(Box::new(DurationToSeconds {}))?;
+
+    Ok(())
+}
+
+/// Returns the current time of the clock
+#[derive(Debug)]
+pub struct Now {
+    clock: Arc<dyn Clock>,
+}
+
+impl ExecutionBlock for Now {
+    fn get_id(&self) -> u32 {
+        71001
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Now"
+    }
+
+    fn get_type(&self) -> ExecutionBlockType {
+        ExecutionBlockType::Static
+    }
+
+    fn get_inputs(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn get_outputs(&self) -> &'static [&'static str] {
+        &["DateTime"]
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn execute(&self, _input: Vec<Register>, block_id: u32) -> Result<Vec<Register>> {
+        Ok(vec![Register {
+            block_id,
+            node_id: 3,
+            value: Box::new(self.clock.now()?),
+        }])
+    }
+}
+
+/// Returns the current date of the clock in UTC
+#[derive(Debug)]
+pub struct Today {
+    clock: Arc<dyn Clock>,
+}
+
+impl ExecutionBlock for Today {
+    fn get_id(&self) -> u32 {
+        71002
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Today"
+    }
+
+    fn get_type(&self) -> ExecutionBlockType {
+        ExecutionBlockType::Static
+    }
+
+    fn get_inputs(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn get_outputs(&self) -> &'static [&'static str] {
+        &["Date"]
+    }
+
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    fn execute(&self, _input: Vec<Register>, block_id: u32) -> Result<Vec<Register>> {
+        Ok(vec![Register {
+            block_id,
+            node_id: 3,
+            value: Box::new(self.clock.now()?.date()),
+        }])
+    }
+}
+
+ExecutionBlockHelper!(
+    id: 71003,
+    name: DateCreate,
+    typ: Static,
+    path: crate,
+
+    fn execute(year: Integer, month: Integer, day: Integer) -> Result<(Date)> {
+        Date::from_ymd(year, month, day)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71004,
+    name: DateAddDays,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date, days: Integer) -> Result<(Date)> {
+        date.add_days(days)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71005,
+    name: DateTimeAddDuration,
+    typ: Static,
+    path: crate,
+
+    fn execute(time: DateTime, duration: Duration) -> Result<(DateTime)> {
+        time.add(&duration)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71006,
+    name: DateDifference,
+    typ: Static,
+    path: crate,
+
+    fn execute(from: Date, to: Date) -> (Integer) {
+        (to.days - from.days)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71007,
+    name: DateTimeDifference,
+    typ: Static,
+    path: crate,
+
+    fn execute(from: DateTime, to: DateTime) -> Result<(Duration)> {
+        from.until(&to)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71008,
+    name: DateDayOfWeek,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date) -> (Integer) {
+        (date.day_of_week())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71009,
+    name: DateStartOfMonth,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date) -> (Date) {
+        (date.start_of_month())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71010,
+    name: DateAddBusinessDays,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date, days: Integer) -> Result<(Date)> {
+        date.add_business_days(days)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71011,
+    name: DateBusinessDaysBetween,
+    typ: Static,
+    path: crate,
+
+    fn execute(from: Date, to: Date) -> (Integer) {
+        (from.business_days_until(&to))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71012,
+    name: DateYearsBetween,
+    typ: Static,
+    path: crate,
+
+    fn execute(from: Date, to: Date) -> (Integer) {
+        (from.years_until(&to))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71013,
+    name: DateFormat,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date, pattern: String) -> Result<(String)> {
+        Ok(format(&date.to_date_time()?, &pattern))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71014,
+    name: DateTimeFormat,
+    typ: Static,
+    path: crate,
+
+    fn execute(time: DateTime, pattern: String) -> (String) {
+        (format(&time, &pattern))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71015,
+    name: DateTimeToDate,
+    typ: Static,
+    path: crate,
+
+    fn execute(time: DateTime) -> (Date) {
+        (time.date())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71016,
+    name: DateToDateTime,
+    typ: Static,
+    path: crate,
+
+    fn execute(date: Date) -> Result<(DateTime)> {
+        date.to_date_time()
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71017,
+    name: DurationCreate,
+    typ: Static,
+    path: crate,
+
+    fn execute(days: Integer, hours: Integer, minutes: Integer, seconds: Integer) -> Result<(Duration)> {
+        Duration::from_parts(days, hours, minutes, seconds)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 71018,
+    name: DurationToSeconds,
+    typ: Static,
+    path: crate,
+
+    fn execute(duration: Duration) -> (Integer) {
+        (duration.millis / 1000)
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::execute_block;
+    use serde_json::json;
+
+    fn date(s: &str) -> Date {
+        Date::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_dates() {
+        for s in &[
+            "2020-02-29",
+            "1970-01-01",
+            "0000-03-01",
+            "9999-12-31",
+            "-0001-12-31",
+            "+10000-01-01",
+            "-271821-04-20",
+        ] {
+            assert_eq!(date(s).to_string(), *s);
+        }
+
+        assert_eq!(date(" +2020-01-02 ").to_string(), "2020-01-02");
+        assert_eq!(date("1969-12-31").days, -1);
+
+        for s in &[
+            "2021-02-29",
+            "2020-13-01",
+            "20-01-01",
+            "10000-01-01",
+            "+1000000-01-01",
+            "+275761-01-01",
+            "2020-1-01",
+        ] {
+            assert!(Date::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_and_display_date_times() {
+        let time = |s| DateTime::parse(s).unwrap().to_string();
+
+        assert_eq!(
+            time("2020-02-29T13:45:00.250+01:00"),
+            "2020-02-29T12:45:00.250Z"
+        );
+        assert_eq!(time("2020-02-29 13:45"), "2020-02-29T13:45:00Z");
+        assert_eq!(time("2020-02-29"), "2020-02-29T00:00:00Z");
+        assert_eq!(time("+10000-01-01T00:00:00Z"), "+10000-01-01T00:00:00Z");
+        assert_eq!(time("-0001-12-31T23:59:59-0100"), "0000-01-01T00:59:59Z");
+
+        for s in &[
+            "2020-02-29T25:00",
+            "2020-02-29T13",
+            "2020-02-29T13:45+1",
+            "2020-02-29X",
+        ] {
+            assert!(DateTime::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_and_display_durations() {
+        let duration = |s| Duration::parse(s).unwrap();
+
+        assert_eq!(duration("P1DT2H30M").to_string(), "P1DT2H30M");
+        assert_eq!(duration("-PT0.5S").millis, -500);
+        assert_eq!(duration("PT36H").to_string(), "P1DT12H");
+        assert_eq!(Duration::new(0).to_string(), "PT0S");
+
+        for s in &["P1Y", "P1M", "1D", "PT", "P1H", "PT1D"] {
+            assert!(Duration::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn calendar() {
+        // 2021-01-01 was a friday
+        assert_eq!(date("2021-01-01").day_of_week(), 5);
+        assert_eq!(date("2021-01-31").start_of_month(), date("2021-01-01"));
+
+        let add = |s, days| date(s).add_business_days(days).unwrap().to_string();
+        assert_eq!(add("2021-01-01", 1), "2021-01-04");
+        assert_eq!(add("2021-01-02", 1), "2021-01-04");
+        assert_eq!(add("2021-01-04", -1), "2021-01-01");
+        assert_eq!(add("2021-01-04", 10), "2021-01-18");
+
+        assert_eq!(
+            date("2021-01-01").business_days_until(&date("2021-01-08")),
+            5
+        );
+        assert_eq!(
+            date("2021-01-08").business_days_until(&date("2021-01-01")),
+            -5
+        );
+
+        assert_eq!(date("2000-02-29").years_until(&date("2001-02-28")), 0);
+        assert_eq!(date("2000-02-29").years_until(&date("2004-02-29")), 4);
+
+        assert!(Date::new(MAX_DAYS).add_days(1).is_err());
+    }
+
+    #[test]
+    fn clock_of_the_logic() {
+        let mut logic = crate::Logic::default();
+        let now = DateTime::parse("2021-06-15T08:30:00Z").unwrap();
+        set_clock(&mut logic, Arc::new(FixedClock(now))).unwrap();
+
+        let today = logic.get_block(71002).unwrap();
+        assert_eq!(
+            execute_block(today, vec![]).unwrap(),
+            vec![json!("2021-06-15")]
+        );
+
+        let now = logic.get_block(71001).unwrap();
+        assert_eq!(
+            execute_block(now, vec![]).unwrap(),
+            vec![json!("2021-06-15T08:30:00Z")]
+        );
+    }
+}
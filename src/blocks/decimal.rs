@@ -0,0 +1,632 @@
+use crate::blocks::float::Float;
+use crate::blocks::integer::Integer;
+use crate::error::{Error, Result};
+use bme_macro::ExecutionBlockHelper;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Fixed-point decimal number, the value is `value / 10^scale`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Decimal {
+    pub value: i128,
+    pub scale: u32,
+}
+
+/// Rounding modes for decimals, when digits need to be cut off
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundingMode {
+    /// Round to the nearest neighbour, ties to the even neighbour
+    HalfEven,
+    /// Round to the nearest neighbour, ties away from zero
+    HalfUp,
+    /// Round towards zero
+    Down,
+}
+
+impl FromStr for RoundingMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mode = s
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+
+        match mode.as_str() {
+            "halfeven" => Ok(RoundingMode::HalfEven),
+            "halfup" => Ok(RoundingMode::HalfUp),
+            "down" => Ok(RoundingMode::Down),
+            _ => Err(format!(
+                "The rounding mode {} is not available, use half-even, half-up or down",
+                s
+            )
+            .into()),
+        }
+    }
+}
+
+impl Decimal {
+    /// Highest scale a decimal can have
+    pub const MAX_SCALE: u32 = 28;
+
+    pub fn new(value: i128, scale: u32) -> Decimal {
+        Decimal { value, scale }
+    }
+
+    /// Parses a decimal like `-12.345` or `1.5e3`. The scale is the number
+    /// of digits given after the point, reduced by the exponent.
+    pub fn parse(s: &str) -> Result<Decimal> {
+        let err = || Error::from(format!("{} is not a valid Decimal", s));
+        let s = s.trim();
+
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i64>().map_err(|_| err())?),
+            None => (s, 0),
+        };
+
+        let (negative, digits) = match mantissa.strip_prefix('-') {
+            Some(d) => (true, d),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+
+        let (int, frac) = match digits.find('.') {
+            Some(i) => (&digits[..i], &digits[i + 1..]),
+            None => (digits, ""),
+        };
+
+        if int.is_empty() || !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(err());
+        }
+
+        let mut value = format!("{}{}", int, frac)
+            .parse::<i128>()
+            .map_err(|_| err())?;
+
+        // a positive exponent beyond the digits given shifts the value itself
+        let scale = (frac.len() as i64).checked_sub(exponent).ok_or_else(err)?;
+        if scale < 0 {
+            value = u32::try_from(-scale)
+                .ok()
+                .and_then(|e| 10i128.checked_pow(e))
+                .and_then(|f| value.checked_mul(f))
+                .ok_or_else(err)?;
+        }
+
+        if scale > Decimal::MAX_SCALE as i64 {
+            return Err(err());
+        }
+
+        Ok(Decimal::new(
+            if negative { -value } else { value },
+            scale.max(0) as u32,
+        ))
+    }
+
+    pub fn from_integer(value: Integer) -> Decimal {
+        Decimal::new(value as i128, 0)
+    }
+
+    /// Converts a float into a decimal with the given scale
+    pub fn from_float(value: Float, scale: u32, mode: RoundingMode) -> Result<Decimal> {
+        if !value.is_finite() {
+            return Err(format!("{} can't be converted into a Decimal", value).into());
+        }
+
+        // the shortest representation of the float, which reads back the same float
+        let s = value.to_string();
+        let digits = s.find('.').map(|i| s.len() - i - 1).unwrap_or_default();
+
+        if digits > Decimal::MAX_SCALE as usize {
+            Decimal::parse(&format!("{:.*}", Decimal::MAX_SCALE as usize, value))?
+                .rescale(scale, mode)
+        } else {
+            Decimal::parse(&s)?.rescale(scale, mode)
+        }
+    }
+
+    pub fn to_float(&self) -> Float {
+        self.value as Float / 10f64.powi(self.scale as i32)
+    }
+
+    /// Converts the decimal into an integer, rounded with the given mode
+    pub fn to_integer(&self, mode: RoundingMode) -> Result<Integer> {
+        let value = self.rescale(0, mode)?.value;
+        Integer::try_from(value).map_err(|_| "The Decimal is too large for an Integer".into())
+    }
+
+    /// Changes the scale of the decimal, cut off digits are rounded with the given mode
+    pub fn rescale(&self, scale: u32, mode: RoundingMode) -> Result<Decimal> {
+        if scale > Decimal::MAX_SCALE {
+            return Err(format!("The scale can't be larger than {}", Decimal::MAX_SCALE).into());
+        }
+
+        let value = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.value,
+            Ordering::Greater => self
+                .value
+                .checked_mul(pow10(scale - self.scale)?)
+                .ok_or_else(overflow)?,
+            Ordering::Less => div_round(self.value, pow10(self.scale - scale)?, mode),
+        };
+
+        Ok(Decimal::new(value, scale))
+    }
+
+    pub fn add(&self, other: &Decimal) -> Result<Decimal> {
+        let (a, b, scale) = self.align(other)?;
+        Ok(Decimal::new(a.checked_add(b).ok_or_else(overflow)?, scale))
+    }
+
+    pub fn subtract(&self, other: &Decimal) -> Result<Decimal> {
+        let (a, b, scale) = self.align(other)?;
+        Ok(Decimal::new(a.checked_sub(b).ok_or_else(overflow)?, scale))
+    }
+
+    /// Multiplies without rounding, as long as the scale allows it
+    pub fn multiply(&self, other: &Decimal) -> Result<Decimal> {
+        let value = self.value.checked_mul(other.value).ok_or_else(overflow)?;
+        let result = Decimal::new(value, self.scale + other.scale);
+
+        if result.scale > Decimal::MAX_SCALE {
+            return result.rescale(Decimal::MAX_SCALE, RoundingMode::HalfEven);
+        }
+
+        Ok(result)
+    }
+
+    /// Divides with the result rounded to the given scale
+    pub fn divide(&self, other: &Decimal, scale: u32, mode: RoundingMode) -> Result<Decimal> {
+        if other.value == 0 {
+            return Err(Error::DivisionByZero);
+        }
+
+        if scale > Decimal::MAX_SCALE {
+            return Err(format!("The scale can't be larger than {}", Decimal::MAX_SCALE).into());
+        }
+
+        // value = (a / 10^sa) / (b / 10^sb) * 10^scale
+        let exp = scale as i64 + other.scale as i64 - self.scale as i64;
+        let (n, d) = if exp >= 0 {
+            let n = self.value.checked_mul(pow10(exp as u32)?);
+            (n.ok_or_else(overflow)?, other.value)
+        } else {
+            let d = other.value.checked_mul(pow10((-exp) as u32)?);
+            (self.value, d.ok_or_else(overflow)?)
+        };
+
+        Ok(Decimal::new(div_round(n, d, mode), scale))
+    }
+
+    /// Formats the decimal with the given separators, like `1,234.50`
+    pub fn format(&self, decimal_separator: &str, group_separator: &str) -> String {
+        let s = self.to_string();
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(s) => ("-", s),
+            None => ("", s.as_str()),
+        };
+
+        let (int, frac) = match s.find('.') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+
+        let mut grouped = String::new();
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                grouped.push_str(group_separator);
+            }
+            grouped.push(c);
+        }
+
+        match frac {
+            Some(f) => format!("{}{}{}{}", sign, grouped, decimal_separator, f),
+            None => format!("{}{}", sign, grouped),
+        }
+    }
+
+    /// Returns both values with the same scale
+    fn align(&self, other: &Decimal) -> Result<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        let a = self.rescale(scale, RoundingMode::Down)?;
+        let b = other.rescale(scale, RoundingMode::Down)?;
+        Ok((a.value, b.value, scale))
+    }
+}
+
+fn overflow() -> Error {
+    "The Decimal is out of range".into()
+}
+
+fn pow10(exp: u32) -> Result<i128> {
+    10i128.checked_pow(exp).ok_or_else(overflow)
+}
+
+/// Divides two integers and rounds the result with the given mode
+fn div_round(n: i128, d: i128, mode: RoundingMode) -> i128 {
+    let q = n / d;
+    let r = (n % d).abs();
+    let negative = (n < 0) != (d < 0);
+    let d = d.abs();
+
+    if r == 0 {
+        return q;
+    }
+
+    let round_away = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::HalfUp => r >= d - r,
+        RoundingMode::HalfEven => r > d - r || (r == d - r && q % 2 != 0),
+    };
+
+    // round away from zero
+    match (round_away, negative) {
+        (false, _) => q,
+        (true, false) => q + 1,
+        (true, true) => q - 1,
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match self.align(other) {
+            Ok((a, b, _)) => a.cmp(&b),
+            // when the values can't be aligned, the digits don't matter
+            Err(_) => self
+                .to_float()
+                .partial_cmp(&other.to_float())
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let digits = self.value.unsigned_abs().to_string();
+        let sign = if self.value < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+impl crate::types::ExecutionType for Decimal {
+    fn get_name(&self) -> &'static str {
+        "Decimal"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn crate::types::ExecutionType>> {
+        match json {
+            serde_json::Value::Number(n) => Ok(Box::new(Decimal::parse(&n.to_string())?)),
+            serde_json::Value::String(s) => Ok(Box::new(Decimal::parse(&s)?)),
+            j => Err(format!("{} is not a valid Decimal", j).into()),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        // as string, to not lose any digits
+        serde_json::json!(self.to_string())
+    }
+
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
+        Box::new(*self)
+    }
+
+    fn get_color(&self) -> &'static str {
+        "gold"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("0.00")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(r"^\s*[-+]?[0-9]+(\.[0-9]*)?([eE][-+]?[0-9]+)?\s*$")
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+/// Converts a scale given as integer input
+fn get_scale(scale: Integer) -> Result<u32> {
+    u32::try_from(scale)
+        .ok()
+        .filter(|s| *s <= Decimal::MAX_SCALE)
+        .ok_or_else(|| {
+            format!(
+                "The scale {} is not between 0 and {}",
+                scale,
+                Decimal::MAX_SCALE
+            )
+            .into()
+        })
+}
+
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(DecimalAdd {}))?;
+    logic.add_block(Box::new(DecimalSubtract {}))?;
+    logic.add_block(Box::new(DecimalMultiply {}))?;
+    logic.add_block(Box::new(DecimalDivide {}))?;
+    logic.add_block(Box::new(DecimalEqual {}))?;
+    logic.add_block(Box::new(DecimalNotEqual {}))?;
+    logic.add_block(Box::new(DecimalLess {}))?;
+    logic.add_block(Box::new(DecimalGreater {}))?;
+    logic.add_block(Box::new(DecimalRound {}))?;
+    logic.add_block(Box::new(DecimalFormat {}))?;
+    logic.add_block(Box::new(IntegerToDecimal {}))?;
+    logic.add_block(Box::new(DecimalToInteger {}))?;
+    logic.add_block(Box::new(FloatToDecimal {}))?;
+    logic.add_block(Box::new(DecimalToFloat {}))?;
+    logic.add_block(Box::new(StringToDecimal {}))?;
+    logic.add_block(Box::new(DecimalToString {}))?;
+
+    Ok(())
+}
+
+ExecutionBlockHelper!(
+    id: 70001,
+    name: DecimalAdd,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> Result<(Decimal)> {
+        inp1.add(&inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70002,
+    name: DecimalSubtract,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> Result<(Decimal)> {
+        inp1.subtract(&inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70003,
+    name: DecimalMultiply,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> Result<(Decimal)> {
+        inp1.multiply(&inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70004,
+    name: DecimalDivide,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal, scale: Integer, mode: String) -> Result<(Decimal)> {
+        inp1.divide(&inp2, get_scale(scale)?, mode.parse()?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70005,
+    name: DecimalEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> (Boolean) {
+        (inp1 == inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70006,
+    name: DecimalNotEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> (Boolean) {
+        (inp1 != inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70007,
+    name: DecimalLess,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> (Boolean) {
+        (inp1 < inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70008,
+    name: DecimalGreater,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Decimal, inp2: Decimal) -> (Boolean) {
+        (inp1 > inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70009,
+    name: DecimalRound,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Decimal, scale: Integer, mode: String) -> Result<(Decimal)> {
+        inp.rescale(get_scale(scale)?, mode.parse()?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70010,
+    name: DecimalFormat,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Decimal, scale: Integer, mode: String, decimal_separator: String, group_separator: String) -> Result<(String)> {
+        let inp = inp.rescale(get_scale(scale)?, mode.parse()?)?;
+        Ok(inp.format(&decimal_separator, &group_separator))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70011,
+    name: IntegerToDecimal,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Integer) -> (Decimal) {
+        (Decimal::from_integer(inp))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70012,
+    name: DecimalToInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Decimal, mode: String) -> Result<(Integer)> {
+        inp.to_integer(mode.parse()?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70013,
+    name: FloatToDecimal,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Float, scale: Integer, mode: String) -> Result<(Decimal)> {
+        Decimal::from_float(inp, get_scale(scale)?, mode.parse()?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70014,
+    name: DecimalToFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Decimal) -> (Float) {
+        (inp.to_float())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70015,
+    name: StringToDecimal,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: String) -> Result<(Decimal)> {
+        Decimal::parse(&inp)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 70016,
+    name: DecimalToString,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: Decimal) -> (String) {
+        (inp.to_string())
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(s: &str, scale: u32, mode: &str) -> String {
+        let mode = mode.parse().unwrap();
+        Decimal::parse(s)
+            .unwrap()
+            .rescale(scale, mode)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn rounding_modes() {
+        for &(value, half_even, half_up, down) in &[
+            ("2.5", "2", "3", "2"),
+            ("-2.5", "-2", "-3", "-2"),
+            ("3.5", "4", "4", "3"),
+            ("-3.5", "-4", "-4", "-3"),
+            ("2.51", "3", "3", "2"),
+            ("-2.49", "-2", "-2", "-2"),
+        ] {
+            assert_eq!(round(value, 0, "half-even"), half_even, "{}", value);
+            assert_eq!(round(value, 0, "HalfUp"), half_up, "{}", value);
+            assert_eq!(round(value, 0, "down"), down, "{}", value);
+        }
+
+        assert_eq!(round("1.005", 2, "half-even"), "1.00");
+        assert_eq!(round("1.005", 2, "half-up"), "1.01");
+        assert_eq!(round("1.5", 3, "down"), "1.500");
+        assert!("nearest".parse::<RoundingMode>().is_err());
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let parse = |s| Decimal::parse(s).map(|d| d.to_string());
+
+        assert_eq!(parse("-12.340").unwrap(), "-12.340");
+        assert_eq!(parse("0.05").unwrap(), "0.05");
+        assert_eq!(parse("1.5e3").unwrap(), "1500");
+        assert_eq!(parse("15E-4").unwrap(), "0.0015");
+        assert_eq!(parse("-2.5e-1").unwrap(), "-0.25");
+
+        for s in &["", "-", "1.2.3", "1e", "1e1.5", "abc", "1e40", "1e-40"] {
+            assert!(parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let d = |s| Decimal::parse(s).unwrap();
+
+        assert_eq!(d("0.1").add(&d("0.2")).unwrap().to_string(), "0.3");
+        assert_eq!(d("1").subtract(&d("0.01")).unwrap().to_string(), "0.99");
+        assert_eq!(d("1.5").multiply(&d("-0.5")).unwrap().to_string(), "-0.75");
+
+        let third = d("1").divide(&d("3"), 4, RoundingMode::HalfEven).unwrap();
+        assert_eq!(third.to_string(), "0.3333");
+
+        assert!(matches!(
+            d("1").divide(&d("0.0"), 2, RoundingMode::HalfEven),
+            Err(Error::DivisionByZero)
+        ));
+        assert_eq!(d("-1234567.891").format(",", "."), "-1.234.567,891");
+    }
+}
@@ -1,30 +1,53 @@
+use crate::error::Result;
 use bme_macro::ExecutionBlockHelper;
 
 pub type Float = f64;
 
+/// Regex of a float as `Float::from_str` reads it, like `-1.5e3`, `.5`, `+1`,
+/// `inf` or `NaN`, to build the value checks of the editor from
+macro_rules! float_pattern {
+    () => {
+        r"[-+]?(([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][-+]?[0-9]+)?|[iI][nN][fF]([iI][nN][iI][tT][yY])?|[nN][aA][nN])"
+    };
+}
+pub(crate) use float_pattern;
+
+/// Json has no NaN or infinity, they are given as the string `from_json` reads back
+pub(crate) fn float_to_json(value: Float) -> serde_json::Value {
+    if value.is_finite() {
+        serde_json::json!(value)
+    } else {
+        serde_json::json!(value.to_string())
+    }
+}
+
 impl crate::types::ExecutionType for Float {
     fn get_name(&self) -> &'static str {
         "Float"
     }
 
-    fn from_json(&self, json: serde_json::Value) -> Box<crate::types::ExecutionType> {
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn crate::types::ExecutionType>> {
         use std::str::FromStr;
 
         if let Ok(s) = serde_json::from_value::<Float>(json.clone()) {
-            return Box::new(s);
+            return Ok(Box::new(s));
         }
 
-        if let Ok(s) = serde_json::from_value::<String>(json) {
-            if let Ok(f) = Float::from_str(&s) {
-                return Box::new(f);
+        if let Ok(s) = serde_json::from_value::<String>(json.clone()) {
+            if let Ok(f) = Float::from_str(s.trim()) {
+                return Ok(Box::new(f));
             }
         }
 
-        Box::new(0.0f64)
+        Err(format!("{} is not a valid Float", json).into())
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        float_to_json(*self)
     }
 
-    fn duplicate(&self) -> Box<crate::types::ExecutionType> {
-        Box::new(self.clone())
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
+        Box::new(*self)
     }
 
     fn get_color(&self) -> &'static str {
@@ -35,17 +58,27 @@ impl crate::types::ExecutionType for Float {
         Some("0.0")
     }
 
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(concat!(r"^\s*", float_pattern!(), r"\s*$"))
+    }
+
     fn get_multi_output(&self) -> bool {
         true
     }
 }
 
-pub fn add_blocks(logic: &mut crate::Logic) {
-    logic.add_block(Box::new(FloatAdd {}));
-    logic.add_block(Box::new(FloatSubtract {}));
-    logic.add_block(Box::new(FloatMultiply {}));
-    logic.add_block(Box::new(FloatDivide {}));
-    logic.add_block(Box::new(FloatToString {}));
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(FloatAdd {}))?;
+    logic.add_block(Box::new(FloatSubtract {}))?;
+    logic.add_block(Box::new(FloatMultiply {}))?;
+    logic.add_block(Box::new(FloatDivide {}))?;
+    logic.add_block(Box::new(FloatToString {}))?;
+    logic.add_block(Box::new(FloatEqual {}))?;
+    logic.add_block(Box::new(FloatNotEqual {}))?;
+    logic.add_block(Box::new(FloatLess {}))?;
+    logic.add_block(Box::new(FloatGreater {}))?;
+
+    Ok(())
 }
 
 
@@ -94,7 +127,7 @@ ExecutionBlockHelper!(
 );
 
 ExecutionBlockHelper!(
-    id: 64005,
+    id: 67017,
     name: FloatToString,
     typ: Static,
     path: crate,
@@ -102,4 +135,48 @@ ExecutionBlockHelper!(
     fn execute(inp1: Float) -> (String) {
         (inp1.to_string())
     }
-);
\ No newline at end of file
+);
+
+ExecutionBlockHelper!(
+    id: 67005,
+    name: FloatEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Float, inp2: Float) -> (Boolean) {
+        (inp1 == inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 67006,
+    name: FloatNotEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Float, inp2: Float) -> (Boolean) {
+        (inp1 != inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 67007,
+    name: FloatLess,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Float, inp2: Float) -> (Boolean) {
+        (inp1 < inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 67008,
+    name: FloatGreater,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Float, inp2: Float) -> (Boolean) {
+        (inp1 > inp2)
+    }
+);
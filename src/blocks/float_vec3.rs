@@ -1,5 +1,6 @@
 
-use crate::blocks::float::Float;
+use crate::blocks::float::{float_pattern, float_to_json, Float};
+use crate::error::Result;
 use bme_macro::ExecutionBlockHelper;
 #[derive(Debug, PartialEq, Clone)]
 pub struct FloatVector3 {
@@ -13,11 +14,22 @@ impl crate::types::ExecutionType for FloatVector3 {
         "FloatVector3"
     }
 
-    fn from_json(&self, _json: serde_json::Value) -> Box<crate::types::ExecutionType> {
-        Box::new(FloatVector3::new(0.0, 0.0, 0.0))
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn crate::types::ExecutionType>> {
+        match FloatVector3::parse(&json) {
+            Some(v) => Ok(Box::new(v)),
+            None => Err(format!("{} is not a valid FloatVector3", json).into()),
+        }
     }
 
-    fn duplicate(&self) -> Box<crate::types::ExecutionType> {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "x": float_to_json(self.x),
+            "y": float_to_json(self.y),
+            "z": float_to_json(self.z),
+        })
+    }
+
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
         Box::new(self.clone())
     }
 
@@ -26,7 +38,47 @@ impl crate::types::ExecutionType for FloatVector3 {
     }
 
     fn get_edit_default(&self) -> Option<&'static str> {
-        Some("0.0")
+        Some("0.0,0.0,0.0")
+    }
+
+    fn get_value_check(&self) -> Option<&'static str> {
+        // a number of an array or object is given as json number or string
+        macro_rules! number {
+            () => {
+                concat!(
+                    r#"(-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][-+]?[0-9]+)?|" *"#,
+                    float_pattern!(),
+                    r#" *")"#
+                )
+            };
+        }
+        macro_rules! field {
+            () => {
+                concat!(r#""[xyz]"\s*:\s*"#, number!())
+            };
+        }
+
+        Some(concat!(
+            r"^\s*(",
+            float_pattern!(),
+            r"\s*,\s*",
+            float_pattern!(),
+            r"\s*,\s*",
+            float_pattern!(),
+            r"|\[\s*",
+            number!(),
+            r"\s*,\s*",
+            number!(),
+            r"\s*,\s*",
+            number!(),
+            r"\s*\]|\{\s*",
+            field!(),
+            r"\s*,\s*",
+            field!(),
+            r"\s*,\s*",
+            field!(),
+            r"\s*\})\s*$"
+        ))
     }
 
     fn get_multi_output(&self) -> bool {
@@ -38,11 +90,75 @@ impl FloatVector3 {
     pub fn new(x: Float, y: Float, z: Float) -> FloatVector3 {
         FloatVector3 { x, y, z }
     }
+
+    /// Parses a vector from a `{"x": 1, "y": 2, "z": 3}` object, a `[1, 2, 3]`
+    /// array or a `"1,2,3"` string. The object and array can also be given
+    /// as string, like they are typed into the editor
+    pub fn parse(json: &serde_json::Value) -> Option<FloatVector3> {
+        use serde_json::Value;
+
+        let number = |v: Option<&Value>| match v? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse::<Float>().ok(),
+            _ => None,
+        };
+
+        match json {
+            Value::Object(o) if o.len() == 3 => Some(FloatVector3::new(
+                number(o.get("x"))?,
+                number(o.get("y"))?,
+                number(o.get("z"))?,
+            )),
+            Value::Array(a) => match a.as_slice() {
+                [x, y, z] => Some(FloatVector3::new(
+                    number(Some(x))?,
+                    number(Some(y))?,
+                    number(Some(z))?,
+                )),
+                _ => None,
+            },
+            Value::String(s) if s.trim_start().starts_with(['[', '{']) => {
+                match serde_json::from_str::<Value>(s).ok()? {
+                    v @ Value::Array(_) | v @ Value::Object(_) => FloatVector3::parse(&v),
+                    _ => None,
+                }
+            }
+            Value::String(s) => {
+                let parts = s
+                    .split(',')
+                    .map(|p| p.trim().parse::<Float>().ok())
+                    .collect::<Option<Vec<Float>>>()?;
+
+                match parts.as_slice() {
+                    [x, y, z] => Some(FloatVector3::new(*x, *y, *z)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn length(&self) -> Float {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dot(&self, other: &FloatVector3) -> Float {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
 }
 
-pub fn add_blocks(logic: &mut crate::Logic) {
-    logic.add_block(Box::new(CreateFloatVector3 {}));
-    logic.add_block(Box::new(SplitFloatVector3 {}));
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(CreateFloatVector3 {}))?;
+    logic.add_block(Box::new(SplitFloatVector3 {}))?;
+    logic.add_block(Box::new(FloatVector3Add {}))?;
+    logic.add_block(Box::new(FloatVector3Subtract {}))?;
+    logic.add_block(Box::new(FloatVector3Scale {}))?;
+    logic.add_block(Box::new(FloatVector3Dot {}))?;
+    logic.add_block(Box::new(FloatVector3Cross {}))?;
+    logic.add_block(Box::new(FloatVector3Length {}))?;
+    logic.add_block(Box::new(FloatVector3Normalize {}))?;
+
+    Ok(())
 }
 
 ExecutionBlockHelper!(
@@ -56,69 +172,143 @@ ExecutionBlockHelper!(
     }
 );
 
-/*
 ExecutionBlockHelper!(
-    id: 67001,
+    id: 67009,
+    name: SplitFloatVector3,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: FloatVector3) -> (Float, Float, Float) {
+        (inp.x, inp.y, inp.z)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 67010,
     name: FloatVector3Add,
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Float, inp2: Float) -> (Float) {
-        (inp1 + inp2)
+    fn execute(inp1: FloatVector3, inp2: FloatVector3) -> (FloatVector3) {
+        (FloatVector3::new(inp1.x + inp2.x, inp1.y + inp2.y, inp1.z + inp2.z))
     }
 );
 
 ExecutionBlockHelper!(
-    id: 67002,
-    name: FloatSubtract,
+    id: 67011,
+    name: FloatVector3Subtract,
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Float, inp2: Float) -> (Float) {
-        (inp1 - inp2)
+    fn execute(inp1: FloatVector3, inp2: FloatVector3) -> (FloatVector3) {
+        (FloatVector3::new(inp1.x - inp2.x, inp1.y - inp2.y, inp1.z - inp2.z))
     }
 );
 
 ExecutionBlockHelper!(
-    id: 67003,
-    name: FloatMultiply,
+    id: 67012,
+    name: FloatVector3Scale,
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Float, inp2: Float) -> (Float) {
-        (inp1 * inp2)
+    fn execute(inp: FloatVector3, factor: Float) -> (FloatVector3) {
+        (FloatVector3::new(inp.x * factor, inp.y * factor, inp.z * factor))
     }
 );
 
 ExecutionBlockHelper!(
-    id: 67004,
-    name: FloatDivide,
+    id: 67013,
+    name: FloatVector3Dot,
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Float, inp2: Float) -> (Float) {
-        (inp1 / inp2)
+    fn execute(inp1: FloatVector3, inp2: FloatVector3) -> (Float) {
+        (inp1.dot(&inp2))
     }
 );
 
 ExecutionBlockHelper!(
-    id: 64005,
-    name: FloatToString,
+    id: 67014,
+    name: FloatVector3Cross,
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Float) -> (String) {
-        (inp1.to_string())
+    fn execute(inp1: FloatVector3, inp2: FloatVector3) -> (FloatVector3) {
+        (FloatVector3::new(
+            inp1.y * inp2.z - inp1.z * inp2.y,
+            inp1.z * inp2.x - inp1.x * inp2.z,
+            inp1.x * inp2.y - inp1.y * inp2.x,
+        ))
     }
-);*/
+);
 
 ExecutionBlockHelper!(
-    id: 67009,
-    name: SplitFloatVector3,
+    id: 67015,
+    name: FloatVector3Length,
     typ: Static,
     path: crate,
 
-    fn execute(inp: FloatVector3) -> (Float, Float, Float) {
-        (inp.x, inp.y, inp.z)
+    fn execute(inp: FloatVector3) -> (Float) {
+        (inp.length())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 67016,
+    name: FloatVector3Normalize,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp: FloatVector3) -> Result<(FloatVector3)> {
+        let length = inp.length();
+
+        if length == 0.0 {
+            return Err("A vector with the length zero can't be normalized".into());
+        }
+
+        Ok(FloatVector3::new(inp.x / length, inp.y / length, inp.z / length))
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::execute_block;
+    use crate::types::ExecutionType;
+    use serde_json::json;
+
+    #[test]
+    fn parse_vectors() {
+        let parse = |json| FloatVector3::parse(&json);
+        let expected = Some(FloatVector3::new(1.0, -2.5, 3.0));
+
+        assert_eq!(parse(json!({"x": 1, "y": -2.5, "z": "3"})), expected);
+        assert_eq!(parse(json!([1, -2.5, 3])), expected);
+        assert_eq!(parse(json!(" 1, -2.5 ,3 ")), expected);
+
+        assert_eq!(parse(json!({"x": 1, "y": 2})), None);
+        assert_eq!(parse(json!([1, 2])), None);
+        assert_eq!(parse(json!("1,2,a")), None);
+        assert_eq!(parse(json!(1)), None);
     }
-);
\ No newline at end of file
+
+    #[test]
+    fn vector_blocks() {
+        let vector = |x, y, z| -> Box<dyn ExecutionType> { Box::new(FloatVector3::new(x, y, z)) };
+
+        let cross = vec![vector(1.0, 0.0, 0.0), vector(0.0, 1.0, 0.0)];
+        assert_eq!(
+            execute_block(&FloatVector3Cross {}, cross).unwrap(),
+            vec![json!({"x": 0.0, "y": 0.0, "z": 1.0})]
+        );
+
+        let length = vec![vector(3.0, 0.0, 4.0)];
+        assert_eq!(
+            execute_block(&FloatVector3Length {}, length).unwrap(),
+            vec![json!(5.0)]
+        );
+
+        let zero = vec![vector(0.0, 0.0, 0.0)];
+        assert!(execute_block(&FloatVector3Normalize {}, zero).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use crate::blocks::boolean::Boolean;
+use crate::blocks::integer::Integer;
+use crate::blocks::{get_input, LoopBlock};
+use crate::error::Result;
+use crate::types::{ExecutionType, Next};
+use crate::{LoopState, LoopStep, Pin, Register};
+use bme_macro::ExecutionBlockHelper;
+
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(Branch {}))?;
+    logic.add_block(Box::new(Repeat {}))?;
+    logic.add_block(Box::new(ForLoop {}))?;
+    logic.add_block(Box::new(While {}))?;
+    logic.add_block(Box::new(ForEachCharacter {}))?;
+
+    Ok(())
+}
+
+ExecutionBlockHelper!(
+    id: 60_002,
+    name: Branch,
+    typ: Normal,
+    next: [True, False],
+    path: crate,
+
+    fn execute(condition: Boolean) -> (Next) {
+        if condition {
+            Next(0)
+        } else {
+            Next(1)
+        }
+    }
+);
+
+/// Runs the loop body `count` times, with the index of the current run as output
+#[derive(Debug)]
+pub struct Repeat {}
+
+impl LoopBlock for Repeat {
+    fn id(&self) -> u32 {
+        60_003
+    }
+
+    fn name(&self) -> &'static str {
+        "Repeat"
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        &["Integer"]
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["Integer"]
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("count")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("index")];
+        PINS
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        let count: Integer = get_input(&input, 0, block_id, self.id(), "Integer")?;
+
+        if (state.iteration as Integer) < count {
+            return Ok(LoopStep::Body(vec![Box::new(state.iteration as Integer)]));
+        }
+
+        Ok(LoopStep::Completed(vec![]))
+    }
+}
+
+/// Runs the loop body for every index from `first` to `last`, both included
+#[derive(Debug)]
+pub struct ForLoop {}
+
+impl LoopBlock for ForLoop {
+    fn id(&self) -> u32 {
+        60_004
+    }
+
+    fn name(&self) -> &'static str {
+        "ForLoop"
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        &["Integer", "Integer"]
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["Integer"]
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("first"), Pin::new("last")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("index")];
+        PINS
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        let first: Integer = get_input(&input, 0, block_id, self.id(), "Integer")?;
+        let last: Integer = get_input(&input, 1, block_id, self.id(), "Integer")?;
+        let index = first.saturating_add(state.iteration as Integer);
+
+        if first <= last && index <= last {
+            return Ok(LoopStep::Body(vec![Box::new(index)]));
+        }
+
+        Ok(LoopStep::Completed(vec![]))
+    }
+}
+
+/// Runs the loop body as long as the condition is true, the condition is
+/// evaluated again before every run
+#[derive(Debug)]
+pub struct While {}
+
+impl LoopBlock for While {
+    fn id(&self) -> u32 {
+        60_005
+    }
+
+    fn name(&self) -> &'static str {
+        "While"
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        &["Boolean"]
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["Integer"]
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("condition")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("index")];
+        PINS
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        let condition: Boolean = get_input(&input, 0, block_id, self.id(), "Boolean")?;
+
+        if condition {
+            return Ok(LoopStep::Body(vec![Box::new(state.iteration as Integer)]));
+        }
+
+        Ok(LoopStep::Completed(vec![]))
+    }
+}
+
+/// Runs the loop body for every character of the text, with the character
+/// and its index as outputs
+#[derive(Debug)]
+pub struct ForEachCharacter {}
+
+impl LoopBlock for ForEachCharacter {
+    fn id(&self) -> u32 {
+        60_006
+    }
+
+    fn name(&self) -> &'static str {
+        "ForEachCharacter"
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        &["String"]
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        &["String", "Integer"]
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("text")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("character"), Pin::new("index")];
+        PINS
+    }
+
+    fn keeps_inputs(&self) -> bool {
+        true
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        // the text is split into its characters once, later calls get no inputs
+        if state.iteration == 0 {
+            let text: String = get_input(&input, 0, block_id, self.id(), "String")?;
+            state.inputs = text
+                .chars()
+                .map(|c| Box::new(c.to_string()) as Box<dyn ExecutionType>)
+                .collect();
+        }
+
+        if let Some(c) = state.inputs.get(state.iteration) {
+            return Ok(LoopStep::Body(vec![
+                c.duplicate(),
+                Box::new(state.iteration as Integer),
+            ]));
+        }
+
+        Ok(LoopStep::Completed(vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::test_util::{execute, logic, Code};
+    use crate::{Context, Model};
+    use serde_json::json;
+
+    /// Start, a loop block and an end block which gets the index of the loop
+    fn loop_code(block_type_id: u32, inputs: &[(&str, serde_json::Value)]) -> Code {
+        // the completed output follows the inputs and the index output
+        let completed = inputs.len() as u32 * 2 + 3;
+        let mut nodes = vec![
+            (0, "input", "Execution"),
+            (1, "output", "Execution"),
+            (3, "output", "Integer"),
+            (completed, "output", "Execution"),
+        ];
+
+        for (i, (typ, _)) in inputs.iter().enumerate() {
+            nodes.push((i as u32 * 2 + 2, "input", typ));
+        }
+
+        let mut code = Code::new().start(1).block(2, block_type_id, &nodes);
+        for (i, (_, value)) in inputs.iter().enumerate() {
+            code = code.value(2, i as u32 * 2 + 2, value.clone());
+        }
+
+        code.end(3)
+            .connect((1, 0), (2, 0))
+            .connect((2, completed), (3, 0))
+            .connect((2, 3), (3, 2))
+    }
+
+    #[test]
+    fn branch_selects_the_execution_output() {
+        let code = |condition| {
+            Code::new()
+                .start(1)
+                .block(
+                    2,
+                    60_002,
+                    &[
+                        (0, "input", "Execution"),
+                        (1, "output", "Execution"),
+                        (2, "input", "Boolean"),
+                        (5, "output", "Execution"),
+                    ],
+                )
+                .value(2, 2, json!(condition))
+                .end(3)
+                .value(3, 2, json!(1))
+                .end(4)
+                .value(4, 2, json!(2))
+                .connect((1, 0), (2, 0))
+                .connect((2, 1), (3, 0))
+                .connect((2, 5), (4, 0))
+        };
+
+        assert_eq!(execute(&code(true), 0).unwrap(), vec![json!(1)]);
+        assert_eq!(execute(&code(false), 0).unwrap(), vec![json!(2)]);
+    }
+
+    #[test]
+    fn loops_run_their_body() {
+        let code = loop_code(60_003, &[("Integer", json!(3))]);
+        assert_eq!(execute(&code, 0).unwrap(), vec![json!(2)]);
+
+        let code = loop_code(60_004, &[("Integer", json!(5)), ("Integer", json!(7))]);
+        assert_eq!(execute(&code, 0).unwrap(), vec![json!(7)]);
+    }
+
+    #[test]
+    fn for_each_character() {
+        // the index is the second output of the block
+        let code = Code::new()
+            .start(1)
+            .block(
+                2,
+                60_006,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "String"),
+                    (3, "output", "String"),
+                    (5, "output", "Integer"),
+                    (7, "output", "Execution"),
+                ],
+            )
+            .value(2, 2, json!("äbc"))
+            .end(3)
+            .connect((1, 0), (2, 0))
+            .connect((2, 7), (3, 0))
+            .connect((2, 5), (3, 2));
+
+        assert_eq!(execute(&code, 0).unwrap(), vec![json!(2)]);
+    }
+
+    #[test]
+    fn endless_loops_are_stopped() {
+        let code = loop_code(60_005, &[("Boolean", json!(true))]);
+        let model = Model::compile(&code.to_json(), &logic()).unwrap();
+
+        let mut context = Context::new();
+        context.set_max_iterations(10);
+
+        match model.execute_with(&mut context, vec![Box::new(0i64)]) {
+            Err(Error::IterationLimit {
+                block_id: 2,
+                block_type_id: 60_005,
+                limit: 10,
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}
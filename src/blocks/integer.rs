@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use bme_macro::ExecutionBlockHelper;
 
 pub type Integer = i64;
@@ -7,24 +8,28 @@ impl crate::types::ExecutionType for Integer {
         "Integer"
     }
 
-    fn from_json(&self, json: serde_json::Value) -> Box<crate::types::ExecutionType> {
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn crate::types::ExecutionType>> {
         use std::str::FromStr;
 
         if let Ok(s) = serde_json::from_value::<Integer>(json.clone()) {
-            return Box::new(s);
+            return Ok(Box::new(s));
         }
 
-        if let Ok(s) = serde_json::from_value::<String>(json) {
-            if let Ok(f) = Integer::from_str(&s) {
-                return Box::new(f);
+        if let Ok(s) = serde_json::from_value::<String>(json.clone()) {
+            if let Ok(f) = Integer::from_str(s.trim()) {
+                return Ok(Box::new(f));
             }
         }
 
-        Box::new(0i64)
+        Err(format!("{} is not a valid Integer", json).into())
     }
 
-    fn duplicate(&self) -> Box<crate::types::ExecutionType> {
-        Box::new(self.clone())
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
+    }
+
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
+        Box::new(*self)
     }
 
     fn get_color(&self) -> &'static str {
@@ -35,17 +40,35 @@ impl crate::types::ExecutionType for Integer {
         Some("0")
     }
 
+    fn get_value_check(&self) -> Option<&'static str> {
+        Some(r"^\s*[-+]?[0-9]+\s*$")
+    }
+
     fn get_multi_output(&self) -> bool {
         true
     }
 }
 
-pub fn add_blocks(logic: &mut crate::Logic) {
-    logic.add_block(Box::new(IntegerAdd {}));
-    logic.add_block(Box::new(IntegerSubtract {}));
-    logic.add_block(Box::new(IntegerMultiply {}));
-    logic.add_block(Box::new(IntegerDivide {}));
-    logic.add_block(Box::new(IntegerToString {}));
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(IntegerAdd {}))?;
+    logic.add_block(Box::new(IntegerSubtract {}))?;
+    logic.add_block(Box::new(IntegerMultiply {}))?;
+    logic.add_block(Box::new(IntegerDivide {}))?;
+    logic.add_block(Box::new(IntegerToString {}))?;
+    logic.add_block(Box::new(IntegerEqual {}))?;
+    logic.add_block(Box::new(IntegerNotEqual {}))?;
+    logic.add_block(Box::new(IntegerLess {}))?;
+    logic.add_block(Box::new(IntegerGreater {}))?;
+    logic.add_block(Box::new(IntegerSaturatingAdd {}))?;
+    logic.add_block(Box::new(IntegerSaturatingSubtract {}))?;
+    logic.add_block(Box::new(IntegerSaturatingMultiply {}))?;
+    logic.add_block(Box::new(IntegerSaturatingDivide {}))?;
+    logic.add_block(Box::new(IntegerWrappingAdd {}))?;
+    logic.add_block(Box::new(IntegerWrappingSubtract {}))?;
+    logic.add_block(Box::new(IntegerWrappingMultiply {}))?;
+    logic.add_block(Box::new(IntegerWrappingDivide {}))?;
+
+    Ok(())
 }
 
 ExecutionBlockHelper!(
@@ -54,8 +77,8 @@ ExecutionBlockHelper!(
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
-        (inp1 + inp2)
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        inp1.checked_add(inp2).ok_or(Error::Overflow)
     }
 );
 
@@ -65,8 +88,8 @@ ExecutionBlockHelper!(
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
-        (inp1 - inp2)
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        inp1.checked_sub(inp2).ok_or(Error::Overflow)
     }
 );
 
@@ -76,8 +99,8 @@ ExecutionBlockHelper!(
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
-        (inp1 * inp2)
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        inp1.checked_mul(inp2).ok_or(Error::Overflow)
     }
 );
 
@@ -87,8 +110,12 @@ ExecutionBlockHelper!(
     typ: Static,
     path: crate,
 
-    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
-        (inp1 / inp2)
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        if inp2 == 0 {
+            return Err(Error::DivisionByZero);
+        }
+
+        inp1.checked_div(inp2).ok_or(Error::Overflow)
     }
 );
 
@@ -101,4 +128,236 @@ ExecutionBlockHelper!(
     fn execute(inp1: Integer) -> (String) {
         (inp1.to_string())
     }
-);
\ No newline at end of file
+);
+
+ExecutionBlockHelper!(
+    id: 64006,
+    name: IntegerEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Boolean) {
+        (inp1 == inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64007,
+    name: IntegerNotEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Boolean) {
+        (inp1 != inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64008,
+    name: IntegerLess,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Boolean) {
+        (inp1 < inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64009,
+    name: IntegerGreater,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Boolean) {
+        (inp1 > inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64010,
+    name: IntegerSaturatingAdd,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.saturating_add(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64011,
+    name: IntegerSaturatingSubtract,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.saturating_sub(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64012,
+    name: IntegerSaturatingMultiply,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.saturating_mul(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64013,
+    name: IntegerSaturatingDivide,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        if inp2 == 0 {
+            return Err(Error::DivisionByZero);
+        }
+
+        Ok(inp1.saturating_div(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64014,
+    name: IntegerWrappingAdd,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.wrapping_add(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64015,
+    name: IntegerWrappingSubtract,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.wrapping_sub(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64016,
+    name: IntegerWrappingMultiply,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> (Integer) {
+        (inp1.wrapping_mul(inp2))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 64017,
+    name: IntegerWrappingDivide,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: Integer, inp2: Integer) -> Result<(Integer)> {
+        if inp2 == 0 {
+            return Err(Error::DivisionByZero);
+        }
+
+        Ok(inp1.wrapping_div(inp2))
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::execute_block;
+    use crate::ExecutionBlock;
+    use serde_json::json;
+
+    fn run(block: &dyn ExecutionBlock, a: Integer, b: Integer) -> Result<serde_json::Value> {
+        Ok(execute_block(block, vec![Box::new(a), Box::new(b)])?.remove(0))
+    }
+
+    /// Returns the error the block failed with
+    fn failure(block: &dyn ExecutionBlock, a: Integer, b: Integer) -> Error {
+        match run(block, a, b) {
+            Err(Error::BlockFailed { source, .. }) => *source,
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(run(&IntegerAdd {}, 2, 3).unwrap(), json!(5));
+        assert_eq!(run(&IntegerDivide {}, -7, 2).unwrap(), json!(-3));
+
+        assert!(matches!(
+            failure(&IntegerAdd {}, Integer::MAX, 1),
+            Error::Overflow
+        ));
+        assert!(matches!(
+            failure(&IntegerSubtract {}, Integer::MIN, 1),
+            Error::Overflow
+        ));
+        assert!(matches!(
+            failure(&IntegerMultiply {}, Integer::MAX, 2),
+            Error::Overflow
+        ));
+        assert!(matches!(
+            failure(&IntegerDivide {}, Integer::MIN, -1),
+            Error::Overflow
+        ));
+        assert!(matches!(
+            failure(&IntegerDivide {}, 1, 0),
+            Error::DivisionByZero
+        ));
+    }
+
+    #[test]
+    fn saturating_and_wrapping_arithmetic() {
+        assert_eq!(
+            run(&IntegerSaturatingAdd {}, Integer::MAX, 1).unwrap(),
+            json!(Integer::MAX)
+        );
+        assert_eq!(
+            run(&IntegerSaturatingSubtract {}, Integer::MIN, 1).unwrap(),
+            json!(Integer::MIN)
+        );
+        assert_eq!(
+            run(&IntegerSaturatingMultiply {}, Integer::MIN, 2).unwrap(),
+            json!(Integer::MIN)
+        );
+        assert_eq!(
+            run(&IntegerSaturatingDivide {}, Integer::MIN, -1).unwrap(),
+            json!(Integer::MAX)
+        );
+        assert!(matches!(
+            failure(&IntegerSaturatingDivide {}, 1, 0),
+            Error::DivisionByZero
+        ));
+
+        assert_eq!(
+            run(&IntegerWrappingAdd {}, Integer::MAX, 1).unwrap(),
+            json!(Integer::MIN)
+        );
+        assert_eq!(
+            run(&IntegerWrappingSubtract {}, Integer::MIN, 1).unwrap(),
+            json!(Integer::MAX)
+        );
+        assert_eq!(
+            run(&IntegerWrappingMultiply {}, Integer::MAX, 2).unwrap(),
+            json!(-2)
+        );
+        assert_eq!(
+            run(&IntegerWrappingDivide {}, Integer::MIN, -1).unwrap(),
+            json!(Integer::MIN)
+        );
+        assert!(matches!(
+            failure(&IntegerWrappingDivide {}, 1, 0),
+            Error::DivisionByZero
+        ));
+    }
+}
@@ -0,0 +1,971 @@
+use crate::blocks::boolean::Boolean;
+use crate::blocks::float::Float;
+use crate::blocks::integer::Integer;
+use crate::blocks::{get_input, get_input_node, LoopBlock};
+use crate::error::Result;
+use crate::types::ExecutionType;
+use crate::{LoopState, LoopStep, Pin, Register};
+use bme_macro::ExecutionBlockHelper;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// List of values with any execution type
+#[derive(Debug, Default)]
+pub struct List(pub Vec<Box<dyn ExecutionType>>);
+
+impl Clone for List {
+    fn clone(&self) -> Self {
+        List(self.0.iter().map(|v| v.duplicate()).collect())
+    }
+}
+
+impl List {
+    /// Returns all values of the list, when all of them have the given type
+    pub fn values<T: ExecutionType + Clone>(&self, typ: &str) -> Result<Vec<T>> {
+        self.0.iter().map(|v| get_value(v.as_ref(), typ)).collect()
+    }
+
+    /// Returns the value at the given index, when it has the given type
+    pub fn get<T: ExecutionType + Clone>(&self, index: Integer, typ: &str) -> Result<T> {
+        let value = usize::try_from(index)
+            .ok()
+            .and_then(|i| self.0.get(i))
+            .ok_or_else(|| {
+                format!(
+                    "The index {} is out of range of the list with {} values",
+                    index,
+                    self.0.len()
+                )
+            })?;
+
+        get_value(value.as_ref(), typ)
+    }
+}
+
+fn get_value<T: ExecutionType + Clone>(value: &dyn ExecutionType, typ: &str) -> Result<T> {
+    value.downcast_ref::<T>().cloned().ok_or_else(|| {
+        format!(
+            "The list contains a value of type {} instead of {}",
+            value.get_name(),
+            typ
+        )
+        .into()
+    })
+}
+
+/// Name of the execution type a plain json value is read back as
+fn plain_type(json: &serde_json::Value) -> Option<&'static str> {
+    use serde_json::Value;
+
+    match json {
+        Value::Bool(_) => Some("Boolean"),
+        Value::Number(n) if n.is_i64() => Some("Integer"),
+        Value::Number(_) => Some("Float"),
+        Value::String(_) => Some("String"),
+        Value::Array(_) => Some("List"),
+        _ => None,
+    }
+}
+
+/// Converts a single value of a list into json. Values whose json would be
+/// read back as another type, like a `Decimal` given as string or a `Float`
+/// which is NaN, are stored together with their type as
+/// `{"type": "Decimal", "value": "1.50"}`.
+fn value_to_json(value: &dyn ExecutionType) -> serde_json::Value {
+    let json = value.to_json();
+
+    if plain_type(&json) == Some(value.get_name()) {
+        json
+    } else {
+        serde_json::json!({ "type": value.get_name(), "value": json })
+    }
+}
+
+/// Converts a single json value into the matching execution type. Values
+/// stored with their type are read with that type, which needs to be one
+/// of the built-in types. Values without a matching type, like null or
+/// other objects, return an error.
+fn value_from_json(json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+    use serde_json::Value;
+
+    match json {
+        Value::Bool(b) => Ok(Box::new(b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(Box::new(i)),
+            None => Ok(Box::new(n.as_f64().unwrap_or_default())),
+        },
+        Value::String(s) => Ok(Box::new(s)),
+        Value::Array(a) => Ok(Box::new(list_from_json(a)?)),
+        Value::Object(mut o) if o.len() == 2 && o.contains_key("value") => {
+            let name = match o.get("type") {
+                Some(Value::String(name)) => name.clone(),
+                _ => return Err(format!("{} can't be stored in a List", Value::Object(o)).into()),
+            };
+
+            let typ = crate::built_in_types()
+                .into_iter()
+                .find(|t| t.get_name() == name && !t.is::<crate::types::Execution>())
+                .ok_or_else(|| format!("A List can't hold values of the type {}", name))?;
+
+            typ.from_json(o.remove("value").unwrap_or_default())
+        }
+        j => Err(format!("{} can't be stored in a List", j).into()),
+    }
+}
+
+fn list_from_json(values: Vec<serde_json::Value>) -> Result<List> {
+    values
+        .into_iter()
+        .map(value_from_json)
+        .collect::<Result<Vec<_>>>()
+        .map(List)
+}
+
+/// Compares two values of a list, numbers are compared with numbers
+/// and strings with strings
+fn compare(a: &dyn ExecutionType, b: &dyn ExecutionType) -> Option<Ordering> {
+    let number = |v: &dyn ExecutionType| match v.downcast_ref::<Integer>() {
+        Some(i) => Some(*i as Float),
+        None => v.downcast_ref::<Float>().copied(),
+    };
+
+    if let (Some(a), Some(b)) = (number(a), number(b)) {
+        return a.partial_cmp(&b);
+    }
+
+    if let (Some(a), Some(b)) = (a.downcast_ref::<String>(), b.downcast_ref::<String>()) {
+        return Some(a.cmp(b));
+    }
+
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Boolean>(), b.downcast_ref::<Boolean>()) {
+        return Some(a.cmp(b));
+    }
+
+    None
+}
+
+impl ExecutionType for List {
+    fn get_name(&self) -> &'static str {
+        "List"
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+        // lists typed into the editor are given as string
+        let json = match json {
+            serde_json::Value::String(s) => {
+                serde_json::from_str(&s).map_err(|e| format!("{} is not a valid List: {}", s, e))?
+            }
+            j => j,
+        };
+
+        match json {
+            serde_json::Value::Array(a) => Ok(Box::new(list_from_json(a)?)),
+            j => Err(format!("{} is not a valid List", j).into()),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(self.0.iter().map(|v| value_to_json(v.as_ref())).collect())
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
+        Box::new(self.clone())
+    }
+
+    fn get_color(&self) -> &'static str {
+        "orange"
+    }
+
+    fn get_edit_default(&self) -> Option<&'static str> {
+        Some("[]")
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(ListCreate {}))?;
+    logic.add_block(Box::new(ListLength {}))?;
+    logic.add_block(Box::new(ListSort {}))?;
+    logic.add_block(Box::new(ListAppendInteger {}))?;
+    logic.add_block(Box::new(ListAppendFloat {}))?;
+    logic.add_block(Box::new(ListAppendString {}))?;
+    logic.add_block(Box::new(ListGetInteger {}))?;
+    logic.add_block(Box::new(ListGetFloat {}))?;
+    logic.add_block(Box::new(ListGetString {}))?;
+    logic.add_block(Box::new(ListContainsInteger {}))?;
+    logic.add_block(Box::new(ListContainsFloat {}))?;
+    logic.add_block(Box::new(ListContainsString {}))?;
+    logic.add_block(Box::new(ListSumInteger {}))?;
+    logic.add_block(Box::new(ListSumFloat {}))?;
+    logic.add_block(Box::new(ListMinInteger {}))?;
+    logic.add_block(Box::new(ListMinFloat {}))?;
+    logic.add_block(Box::new(ListMaxInteger {}))?;
+    logic.add_block(Box::new(ListMaxFloat {}))?;
+    logic.add_block(Box::new(ListMap::<Integer>::new(
+        69051,
+        "ListMapInteger",
+        &["List", "Integer"],
+        &["Integer", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListMap::<Float>::new(
+        69052,
+        "ListMapFloat",
+        &["List", "Float"],
+        &["Float", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListMap::<String>::new(
+        69053,
+        "ListMapString",
+        &["List", "String"],
+        &["String", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListFilter::<Integer>::new(
+        69061,
+        "ListFilterInteger",
+        &["List", "Boolean"],
+        &["Integer", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListFilter::<Float>::new(
+        69062,
+        "ListFilterFloat",
+        &["List", "Boolean"],
+        &["Float", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListFilter::<String>::new(
+        69063,
+        "ListFilterString",
+        &["List", "Boolean"],
+        &["String", "Integer", "List"],
+    )))?;
+    logic.add_block(Box::new(ListForEach::<Integer>::new(
+        69071,
+        "ListForEachInteger",
+        &["Integer", "Integer"],
+    )))?;
+    logic.add_block(Box::new(ListForEach::<Float>::new(
+        69072,
+        "ListForEachFloat",
+        &["Float", "Integer"],
+    )))?;
+    logic.add_block(Box::new(ListForEach::<String>::new(
+        69073,
+        "ListForEachString",
+        &["String", "Integer"],
+    )))?;
+
+    Ok(())
+}
+
+ExecutionBlockHelper!(
+    id: 69001,
+    name: ListCreate,
+    typ: Static,
+    path: crate,
+
+    fn execute() -> (List) {
+        (List::default())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69002,
+    name: ListLength,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> (Integer) {
+        (list.0.len() as Integer)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69003,
+    name: ListSort,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(List)> {
+        let mut list = list;
+        let mut error = false;
+
+        list.0.sort_by(|a, b| {
+            compare(a.as_ref(), b.as_ref()).unwrap_or_else(|| {
+                error = true;
+                Ordering::Equal
+            })
+        });
+
+        if error {
+            return Err("Only lists of numbers, strings or booleans can be sorted".into());
+        }
+
+        Ok(list)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69011,
+    name: ListAppendInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: Integer) -> (List) {
+        let mut list = list;
+        list.0.push(Box::new(value));
+        (list)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69012,
+    name: ListAppendFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: Float) -> (List) {
+        let mut list = list;
+        list.0.push(Box::new(value));
+        (list)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69013,
+    name: ListAppendString,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: String) -> (List) {
+        let mut list = list;
+        list.0.push(Box::new(value));
+        (list)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69021,
+    name: ListGetInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, index: Integer) -> Result<(Integer)> {
+        list.get::<Integer>(index, "Integer")
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69022,
+    name: ListGetFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, index: Integer) -> Result<(Float)> {
+        list.get::<Float>(index, "Float")
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69023,
+    name: ListGetString,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, index: Integer) -> Result<(String)> {
+        list.get::<String>(index, "String")
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69031,
+    name: ListContainsInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: Integer) -> (Boolean) {
+        (list.0.iter().any(|v| v.downcast_ref::<Integer>() == Some(&value)))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69032,
+    name: ListContainsFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: Float) -> (Boolean) {
+        (list.0.iter().any(|v| v.downcast_ref::<Float>() == Some(&value)))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69033,
+    name: ListContainsString,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List, value: String) -> (Boolean) {
+        (list.0.iter().any(|v| v.downcast_ref::<String>() == Some(&value)))
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69041,
+    name: ListSumInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Integer)> {
+        let values = list.values::<Integer>("Integer")?;
+        values
+            .iter()
+            .try_fold(0 as Integer, |sum, v| sum.checked_add(*v))
+            .ok_or(crate::error::Error::Overflow)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69042,
+    name: ListSumFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Float)> {
+        Ok(list.values::<Float>("Float")?.iter().sum::<Float>())
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69043,
+    name: ListMinInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Integer)> {
+        Ok(list
+            .values::<Integer>("Integer")?
+            .into_iter()
+            .min()
+            .ok_or("The list is empty")?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69044,
+    name: ListMinFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Float)> {
+        Ok(list
+            .values::<Float>("Float")?
+            .into_iter()
+            .reduce(Float::min)
+            .ok_or("The list is empty")?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69045,
+    name: ListMaxInteger,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Integer)> {
+        Ok(list
+            .values::<Integer>("Integer")?
+            .into_iter()
+            .max()
+            .ok_or("The list is empty")?)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 69046,
+    name: ListMaxFloat,
+    typ: Static,
+    path: crate,
+
+    fn execute(list: List) -> Result<(Float)> {
+        Ok(list
+            .values::<Float>("Float")?
+            .into_iter()
+            .reduce(Float::max)
+            .ok_or("The list is empty")?)
+    }
+);
+
+/// Runs the loop body for every value of the list and collects the value
+/// fed back from the loop body into a new list
+#[derive(Debug)]
+pub struct ListMap<T> {
+    id: u32,
+    name: &'static str,
+    inputs: &'static [&'static str],
+    outputs: &'static [&'static str],
+    element: PhantomData<T>,
+}
+
+impl<T> ListMap<T> {
+    pub fn new(
+        id: u32,
+        name: &'static str,
+        inputs: &'static [&'static str],
+        outputs: &'static [&'static str],
+    ) -> ListMap<T> {
+        ListMap {
+            id,
+            name,
+            inputs,
+            outputs,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<T: ExecutionType + Clone> LoopBlock for ListMap<T> {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        self.inputs
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        self.outputs
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("list"), Pin::new("value")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("element"), Pin::new("index"), Pin::new("result")];
+        PINS
+    }
+
+    fn feedback_inputs(&self) -> usize {
+        1
+    }
+
+    fn keeps_inputs(&self) -> bool {
+        true
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        // the list is kept from the first call, later calls only get the
+        // value for the previous element fed back from the loop body
+        if state.iteration == 0 {
+            let list: List = get_input(&input, 0, block_id, self.id, "List")?;
+            state.inputs = list.0;
+        } else {
+            let value: T = get_input_node(&input, 0, 4, block_id, self.id, self.inputs[1])?;
+            state.values.push(Box::new(value));
+        }
+
+        match state.inputs.get(state.iteration) {
+            Some(v) => Ok(LoopStep::Body(vec![
+                Box::new(get_value::<T>(v.as_ref(), self.outputs[0])?),
+                Box::new(state.iteration as Integer),
+            ])),
+            None => Ok(LoopStep::Completed(vec![Box::new(List(std::mem::take(
+                &mut state.values,
+            )))])),
+        }
+    }
+}
+
+/// Runs the loop body for every value of the list and keeps the values
+/// for which the loop body feeds back true
+#[derive(Debug)]
+pub struct ListFilter<T> {
+    id: u32,
+    name: &'static str,
+    inputs: &'static [&'static str],
+    outputs: &'static [&'static str],
+    element: PhantomData<T>,
+}
+
+impl<T> ListFilter<T> {
+    pub fn new(
+        id: u32,
+        name: &'static str,
+        inputs: &'static [&'static str],
+        outputs: &'static [&'static str],
+    ) -> ListFilter<T> {
+        ListFilter {
+            id,
+            name,
+            inputs,
+            outputs,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<T: ExecutionType + Clone> LoopBlock for ListFilter<T> {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        self.inputs
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        self.outputs
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("list"), Pin::new("keep")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("element"), Pin::new("index"), Pin::new("result")];
+        PINS
+    }
+
+    fn feedback_inputs(&self) -> usize {
+        1
+    }
+
+    fn keeps_inputs(&self) -> bool {
+        true
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        // the list is kept from the first call, later calls only get the
+        // decision for the previous element fed back from the loop body
+        if state.iteration == 0 {
+            let list: List = get_input(&input, 0, block_id, self.id, "List")?;
+            state.inputs = list.0;
+        } else {
+            let keep: Boolean = get_input_node(&input, 0, 4, block_id, self.id, "Boolean")?;
+
+            if keep {
+                let value = state
+                    .inputs
+                    .get(state.iteration - 1)
+                    .ok_or("The element of the previous run is not available")?;
+                state.values.push(value.duplicate());
+            }
+        }
+
+        match state.inputs.get(state.iteration) {
+            Some(v) => Ok(LoopStep::Body(vec![
+                Box::new(get_value::<T>(v.as_ref(), self.outputs[0])?),
+                Box::new(state.iteration as Integer),
+            ])),
+            None => Ok(LoopStep::Completed(vec![Box::new(List(std::mem::take(
+                &mut state.values,
+            )))])),
+        }
+    }
+}
+
+/// Runs the loop body for every value of the list with the value and its index
+#[derive(Debug)]
+pub struct ListForEach<T> {
+    id: u32,
+    name: &'static str,
+    outputs: &'static [&'static str],
+    element: PhantomData<T>,
+}
+
+impl<T> ListForEach<T> {
+    pub fn new(id: u32, name: &'static str, outputs: &'static [&'static str]) -> ListForEach<T> {
+        ListForEach {
+            id,
+            name,
+            outputs,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<T: ExecutionType + Clone> LoopBlock for ListForEach<T> {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn inputs(&self) -> &'static [&'static str] {
+        &["List"]
+    }
+
+    fn outputs(&self) -> &'static [&'static str] {
+        self.outputs
+    }
+
+    fn input_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("list")];
+        PINS
+    }
+
+    fn output_pins(&self) -> &'static [Pin] {
+        const PINS: &[Pin] = &[Pin::new("element"), Pin::new("index")];
+        PINS
+    }
+
+    fn keeps_inputs(&self) -> bool {
+        true
+    }
+
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>> {
+        // the list is kept from the first call, later calls get no inputs
+        if state.iteration == 0 {
+            let list: List = get_input(&input, 0, block_id, self.id, "List")?;
+            state.inputs = list.0;
+        }
+
+        match state.inputs.get(state.iteration) {
+            Some(v) => Ok(LoopStep::Body(vec![
+                Box::new(get_value::<T>(v.as_ref(), self.outputs[0])?),
+                Box::new(state.iteration as Integer),
+            ])),
+            None => Ok(LoopStep::Completed(vec![])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::date::{Date, Duration};
+    use crate::blocks::decimal::Decimal;
+    use crate::blocks::float_vec3::FloatVector3;
+    use crate::test_util::{execute, execute_block, logic, Code};
+    use crate::Model;
+    use serde_json::json;
+
+    ExecutionBlockHelper!(
+        id: 3,
+        name: ListEnd,
+        typ: End,
+        path: crate,
+
+        fn execute(_result: List) -> () {}
+    );
+
+    /// Runs a map or filter block over the list, the loop body compares or
+    /// multiplies every element with the value by the given static block
+    fn run_loop(
+        block_type_id: u32,
+        list: serde_json::Value,
+        body_type_id: u32,
+        body_output: &str,
+        value: i64,
+    ) -> Result<serde_json::Value> {
+        let code = Code::new()
+            .start(1)
+            .block(
+                2,
+                block_type_id,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "List"),
+                    (3, "output", "Integer"),
+                    (4, "input", body_output),
+                    (5, "output", "Integer"),
+                    (7, "output", "List"),
+                    (9, "output", "Execution"),
+                ],
+            )
+            .value(2, 2, list)
+            .block(
+                3,
+                body_type_id,
+                &[
+                    (2, "input", "Integer"),
+                    (3, "output", body_output),
+                    (4, "input", "Integer"),
+                ],
+            )
+            .value(3, 4, json!(value))
+            .block(4, 3, &[(0, "input", "Execution"), (2, "input", "List")])
+            .connect((1, 0), (2, 0))
+            .connect((2, 3), (3, 2))
+            .connect((3, 3), (2, 4))
+            .connect((2, 9), (4, 0))
+            .connect((2, 7), (4, 2));
+
+        let mut logic = logic();
+        logic.add_block(Box::new(ListEnd {}))?;
+
+        let model = Model::compile(&code.to_json(), &logic)?;
+        let values = model
+            .execute(vec![])?
+            .ok_or("The execution ended without reaching an end block")?;
+        Ok(values[0].to_json())
+    }
+
+    #[test]
+    fn map_and_filter() {
+        let result = run_loop(69_051, json!([1, 2, 3]), 64_003, "Integer", 10);
+        assert_eq!(result.unwrap(), json!([10, 20, 30]));
+
+        let result = run_loop(69_061, json!([1, 5, 2, 7]), 64_009, "Boolean", 3);
+        assert_eq!(result.unwrap(), json!([5, 7]));
+
+        let result = run_loop(69_061, json!([]), 64_009, "Boolean", 3);
+        assert_eq!(result.unwrap(), json!([]));
+
+        // the elements need to have the type of the block
+        assert!(run_loop(69_051, json!([1, "a"]), 64_003, "Integer", 10).is_err());
+    }
+
+    #[test]
+    fn for_each() {
+        // the end block gets the element or index of the last run of the body
+        let for_each = |list: serde_json::Value, output: u32| {
+            let code = Code::new()
+                .start(1)
+                .block(
+                    2,
+                    69_071,
+                    &[
+                        (0, "input", "Execution"),
+                        (1, "output", "Execution"),
+                        (2, "input", "List"),
+                        (3, "output", "Integer"),
+                        (5, "output", "Integer"),
+                        (7, "output", "Execution"),
+                    ],
+                )
+                .value(2, 2, list)
+                .block(
+                    3,
+                    60_001,
+                    &[
+                        (0, "input", "Execution"),
+                        (1, "output", "Execution"),
+                        (2, "input", "String"),
+                    ],
+                )
+                .value(3, 2, json!("body"))
+                .end(4)
+                .connect((1, 0), (2, 0))
+                .connect((2, 1), (3, 0))
+                .connect((2, 7), (4, 0))
+                .connect((2, output), (4, 2));
+
+            execute(&code, 0)
+        };
+
+        assert_eq!(for_each(json!([4, 5, 6]), 3).unwrap(), vec![json!(6)]);
+        assert_eq!(for_each(json!([4, 5, 6]), 5).unwrap(), vec![json!(2)]);
+
+        // the elements need to have the type of the block
+        assert!(for_each(json!([4, "a"]), 3).is_err());
+    }
+
+    #[test]
+    fn lists_from_json() {
+        let list = |json| List::default().from_json(json).map(|v| v.to_json());
+
+        let values = json!([1, 2.5, "a", true, [3]]);
+        assert_eq!(list(values.clone()).unwrap(), values);
+        assert_eq!(list(json!("[1, 2]")).unwrap(), json!([1, 2]));
+
+        assert!(list(json!([null])).is_err());
+        assert!(list(json!([[1, {"a": 1}]])).is_err());
+        assert!(list(json!({"a": 1})).is_err());
+        assert!(list(json!("[1,")).is_err());
+        assert!(list(json!([{"type": "Execution", "value": null}])).is_err());
+        assert!(list(json!([{"type": "Customer", "value": {}}])).is_err());
+        assert!(list(json!([{"type": "Date", "value": "1.5.2020"}])).is_err());
+    }
+
+    #[test]
+    fn lists_round_trip_through_json() {
+        let values = List(vec![
+            Box::new(1i64),
+            Box::new(2.0f64),
+            Box::new(f64::NAN),
+            Box::new(f64::INFINITY),
+            Box::new(String::from("NaN")),
+            Box::new(FloatVector3::new(1.0, 2.0, 3.0)),
+            Box::new(Decimal::new(150, 2)),
+            Box::new(Date::new(0)),
+            Box::new(List(vec![Box::new(Duration::new(1_000))])),
+        ]);
+
+        // only the values whose json doesn't tell their type are tagged
+        let json = values.to_json();
+        assert_eq!(json[0], json!(1));
+        assert_eq!(json[1], json!(2.0));
+        assert_eq!(json[2], json!({"type": "Float", "value": "NaN"}));
+        assert_eq!(json[4], json!("NaN"));
+        assert_eq!(json[6], json!({"type": "Decimal", "value": "1.50"}));
+        assert_eq!(json[8][0]["type"], json!("Duration"));
+
+        let parsed = List::default().from_json(json.clone()).unwrap();
+        let parsed = parsed.downcast_ref::<List>().unwrap();
+        assert_eq!(parsed.to_json(), json);
+
+        let names = parsed.0.iter().map(|v| v.get_name()).collect::<Vec<_>>();
+        let expected = values.0.iter().map(|v| v.get_name()).collect::<Vec<_>>();
+        assert_eq!(names, expected);
+        assert!(parsed.0[2].downcast_ref::<Float>().unwrap().is_nan());
+    }
+
+    #[test]
+    fn sort_and_get() {
+        let list = |values: Vec<Box<dyn ExecutionType>>| -> Vec<Box<dyn ExecutionType>> {
+            vec![Box::new(List(values))]
+        };
+
+        let sorted = execute_block(
+            &ListSort {},
+            list(vec![Box::new(3i64), Box::new(1.5f64), Box::new(2i64)]),
+        );
+        assert_eq!(sorted.unwrap(), vec![json!([1.5, 2, 3])]);
+
+        let mixed = list(vec![Box::new(1i64), Box::new(String::from("a"))]);
+        assert!(execute_block(&ListSort {}, mixed).is_err());
+
+        let mut inputs = list(vec![Box::new(4i64)]);
+        inputs.push(Box::new(0i64));
+        assert_eq!(
+            execute_block(&ListGetInteger {}, inputs).unwrap(),
+            vec![json!(4)]
+        );
+
+        let mut inputs = list(vec![Box::new(4i64)]);
+        inputs.push(Box::new(1i64));
+        assert!(execute_block(&ListGetInteger {}, inputs).is_err());
+    }
+}
@@ -1,18 +1,188 @@
+pub mod boolean;
+pub mod date;
+pub mod decimal;
+pub mod flow;
 pub mod integer;
+pub mod list;
+pub mod record;
 pub mod float;
 pub mod float_vec3;
 pub mod string;
 
+use crate::error::{Error, Result};
+use crate::types::ExecutionType;
+use crate::{ExecutionBlock, ExecutionBlockType, LoopState, LoopStep, Pin, Register};
 use bme_macro::ExecutionBlockHelper;
 
-pub fn add_blocks(logic: &mut crate::Logic) {
-    logic.add_block(Box::new(Comment {}));
-    logic.add_block(Box::new(ConsolePrint {}));
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(Comment {}))?;
+    logic.add_block(Box::new(ConsolePrint {}))?;
 
-    integer::add_blocks(logic);
-    float::add_blocks(logic);
-    float_vec3::add_blocks(logic);
-    string::add_blocks(logic);
+    flow::add_blocks(logic)?;
+    boolean::add_blocks(logic)?;
+    integer::add_blocks(logic)?;
+    float::add_blocks(logic)?;
+    float_vec3::add_blocks(logic)?;
+    string::add_blocks(logic)?;
+    list::add_blocks(logic)?;
+    decimal::add_blocks(logic)?;
+    date::add_blocks(logic)?;
+
+    Ok(())
+}
+
+/// Reads the input with the given index for blocks which are not defined
+/// through the `ExecutionBlockHelper` makro.
+pub(crate) fn get_input<T: crate::types::ExecutionType + Clone>(
+    input: &[Register],
+    index: usize,
+    block_id: u32,
+    block_type_id: u32,
+    typ: &str,
+) -> Result<T> {
+    let node_id = (index as u32 * 2) + 2;
+
+    get_input_node(input, index, node_id, block_id, block_type_id, typ)
+}
+
+/// Reads the input with the given index, which belongs to the given node.
+/// Used when not all inputs of the block are given, like for loop blocks
+/// which keep their inputs.
+pub(crate) fn get_input_node<T: crate::types::ExecutionType + Clone>(
+    input: &[Register],
+    index: usize,
+    node_id: u32,
+    block_id: u32,
+    block_type_id: u32,
+    typ: &str,
+) -> Result<T> {
+    let value = input.get(index).ok_or(Error::MissingRegisterValue {
+        block_id,
+        node_id,
+        block_type_id,
+    })?;
+
+    value
+        .value
+        .downcast_ref::<T>()
+        .cloned()
+        .ok_or_else(|| Error::TypeMismatch {
+            block_id,
+            node_id,
+            block_type_id,
+            expected: typ.to_string(),
+            found: value.value.get_name().to_string(),
+        })
+}
+
+/// Loop block given by its nodes and a single step of the loop.
+///
+/// Every loop block gets the rest of its `ExecutionBlock` from it: it is
+/// never executed, but runs the loop body with its first execution output
+/// `LoopBody` and continues with its second one `Completed`.
+pub trait LoopBlock: std::fmt::Debug + Send + Sync {
+    fn id(&self) -> u32;
+    fn name(&self) -> &str;
+
+    /// Connection types of the data inputs, see `ExecutionBlock::get_inputs`
+    fn inputs(&self) -> &'static [&'static str];
+    fn outputs(&self) -> &'static [&'static str];
+    fn input_pins(&self) -> &'static [Pin];
+    fn output_pins(&self) -> &'static [Pin];
+
+    /// See `ExecutionBlock::get_feedback_inputs`
+    fn feedback_inputs(&self) -> usize {
+        0
+    }
+
+    /// See `ExecutionBlock::keeps_inputs`
+    fn keeps_inputs(&self) -> bool {
+        false
+    }
+
+    /// Computes the next step of the loop, see `ExecutionBlock::iterate`.
+    /// The values for a run of the loop body are given to the first data
+    /// outputs, the values of the completed loop to the last ones.
+    fn step(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep<Vec<Box<dyn ExecutionType>>>>;
+}
+
+impl<L: LoopBlock> ExecutionBlock for L {
+    fn get_id(&self) -> u32 {
+        self.id()
+    }
+
+    fn get_name(&self) -> &str {
+        self.name()
+    }
+
+    fn get_type(&self) -> ExecutionBlockType {
+        ExecutionBlockType::Loop
+    }
+
+    fn execute(&self, _input: Vec<Register>, _block_id: u32) -> Result<Vec<Register>> {
+        Err("Loop blocks can't be executed".into())
+    }
+
+    fn iterate(
+        &self,
+        input: Vec<Register>,
+        block_id: u32,
+        state: &mut LoopState,
+    ) -> Result<LoopStep> {
+        // the output with the given index has the node id index * 2 + 3
+        let registers = |first: usize, values: Vec<Box<dyn ExecutionType>>| {
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| Register {
+                    block_id,
+                    node_id: ((first + i) as u32 * 2) + 3,
+                    value,
+                })
+                .collect()
+        };
+
+        Ok(match self.step(input, block_id, state)? {
+            LoopStep::Body(values) => LoopStep::Body(registers(0, values)),
+            LoopStep::Completed(values) => {
+                let first = self.outputs().len().saturating_sub(values.len());
+                LoopStep::Completed(registers(first, values))
+            }
+        })
+    }
+
+    fn get_inputs(&self) -> &'static [&'static str] {
+        self.inputs()
+    }
+
+    fn get_outputs(&self) -> &'static [&'static str] {
+        self.outputs()
+    }
+
+    fn get_input_pins(&self) -> &'static [Pin] {
+        self.input_pins()
+    }
+
+    fn get_output_pins(&self) -> &'static [Pin] {
+        self.output_pins()
+    }
+
+    fn get_next_outputs(&self) -> &'static [&'static str] {
+        &["LoopBody", "Completed"]
+    }
+
+    fn get_feedback_inputs(&self) -> usize {
+        self.feedback_inputs()
+    }
+
+    fn keeps_inputs(&self) -> bool {
+        LoopBlock::keeps_inputs(self)
+    }
 }
 
 ExecutionBlockHelper!(
@@ -21,9 +191,7 @@ ExecutionBlockHelper!(
     typ: Comment,
     path: crate,
 
-    fn execute() -> () {
-        ()
-    }
+    fn execute() -> () {}
 );
 
 ExecutionBlockHelper!(
@@ -39,6 +207,5 @@ ExecutionBlockHelper!(
         else {
             println!("#> {}", inp);
         }
-        ()
     }
 );
\ No newline at end of file
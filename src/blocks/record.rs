@@ -0,0 +1,411 @@
+use crate::error::{Error, Result};
+use crate::types::ExecutionType;
+use crate::{ExecutionBlock, ExecutionBlockType, NodeDefinition, Register};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Schema of a record type declared at runtime, like
+/// `{ "id": 90000, "name": "Customer", "fields": [{ "name": "age", "type": "Integer" }] }`.
+///
+/// The create block of the record gets the given id and the split block the id after it.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RecordSchema {
+    pub id: u32,
+    pub name: String,
+    pub fields: Vec<RecordField>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RecordField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+}
+
+/// Record type declared at runtime, shared by its values and blocks
+#[derive(Debug)]
+pub struct RecordType {
+    name: String,
+    color: String,
+    fields: Vec<RecordField>,
+}
+
+impl RecordType {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name and the type of every field
+    pub fn get_fields(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.typ.as_str()))
+    }
+}
+
+/// Value of a record type, with one value per field
+#[derive(Debug)]
+pub struct Record {
+    pub typ: Arc<RecordType>,
+    pub values: Vec<Box<dyn ExecutionType>>,
+}
+
+impl Clone for Record {
+    fn clone(&self) -> Self {
+        Record {
+            typ: self.typ.clone(),
+            values: self.values.iter().map(|v| v.duplicate()).collect(),
+        }
+    }
+}
+
+impl Record {
+    /// Returns the value of the field with the given name
+    pub fn get(&self, field: &str) -> Option<&dyn ExecutionType> {
+        let index = self.typ.fields.iter().position(|f| f.name == field)?;
+        self.values.get(index).map(|v| v.as_ref())
+    }
+}
+
+impl ExecutionType for Record {
+    fn get_name(&self) -> &str {
+        &self.typ.name
+    }
+
+    fn from_json(&self, json: serde_json::Value) -> Result<Box<dyn ExecutionType>> {
+        // records typed into the editor are given as string
+        let json = match json {
+            serde_json::Value::String(s) => serde_json::from_str(&s)
+                .map_err(|e| format!("{} is not a valid {}: {}", s, self.typ.name, e))?,
+            j => j,
+        };
+
+        if !json.is_object() {
+            return Err(format!("{} is not a valid {}", json, self.typ.name).into());
+        }
+
+        // every field needs a value, like every input
+        let values = self
+            .typ
+            .get_fields()
+            .zip(self.values.iter())
+            .map(|((n, _), v)| match json.get(n) {
+                Some(value) if !value.is_null() => v
+                    .from_json(value.clone())
+                    .map_err(|e| format!("Field {} of {}: {}", n, self.typ.name, e).into()),
+                _ => Err(format!("Field {} of {} is missing", n, self.typ.name).into()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Box::new(Record {
+            typ: self.typ.clone(),
+            values,
+        }))
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.typ
+            .get_fields()
+            .zip(self.values.iter())
+            .map(|((n, _), v)| (n.to_string(), v.to_json()))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
+        Box::new(self.clone())
+    }
+
+    fn get_color(&self) -> &str {
+        &self.typ.color
+    }
+
+    fn get_multi_output(&self) -> bool {
+        true
+    }
+}
+
+/// Registers the record type of the schema, with the blocks to create and
+/// split the record, within the logic
+pub fn add_record_type(logic: &mut crate::Logic, schema: RecordSchema) -> Result<()> {
+    if logic.has_connection_type(&schema.name) {
+        return Err(format!("The execution type {} already exists", schema.name).into());
+    }
+
+    let split_id = schema.id.checked_add(1).ok_or(format!(
+        "The block id {} leaves no id for the split block",
+        schema.id
+    ))?;
+
+    for id in &[schema.id, split_id] {
+        if logic.get_block(*id).is_some() {
+            return Err(format!("The block id {} is already used", id).into());
+        }
+    }
+
+    for (i, field) in schema.fields.iter().enumerate() {
+        if schema.fields[..i].iter().any(|f| f.name == field.name) {
+            return Err(format!(
+                "The field {} of {} is declared twice",
+                field.name, schema.name
+            )
+            .into());
+        }
+    }
+
+    // a value of every field type, which converts the json of the field
+    let values = schema
+        .fields
+        .iter()
+        .map(|f| match logic.get_connection_type(&f.typ) {
+            Some(t) => Ok(t.duplicate()),
+            None => Err(format!(
+                "The execution type {} of the field {} doesn't exist",
+                f.typ, f.name
+            )
+            .into()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let typ = Arc::new(RecordType {
+        color: schema.color.unwrap_or_else(|| String::from("grey")),
+        fields: schema.fields,
+        name: schema.name,
+    });
+
+    logic.add_connection_type(Box::new(Record {
+        typ: typ.clone(),
+        values,
+    }));
+    logic.add_block(Box::new(CreateRecord {
+        id: schema.id,
+        name: format!("Create{}", typ.name),
+        typ: typ.clone(),
+    }))?;
+    logic.add_block(Box::new(SplitRecord {
+        id: split_id,
+        name: format!("Split{}", typ.name),
+        typ,
+    }))?;
+
+    Ok(())
+}
+
+/// Creates a record out of the values of its fields
+#[derive(Debug)]
+pub struct CreateRecord {
+    id: u32,
+    name: String,
+    typ: Arc<RecordType>,
+}
+
+impl ExecutionBlock for CreateRecord {
+    fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_type(&self) -> ExecutionBlockType {
+        ExecutionBlockType::Static
+    }
+
+    fn get_nodes(&self) -> Vec<NodeDefinition<'_>> {
+        let mut nodes = self
+            .typ
+            .get_fields()
+            .enumerate()
+            .map(|(i, (n, t))| NodeDefinition::new((i as u32 * 2) + 2, "input", t, Some(n)))
+            .collect::<Vec<_>>();
+
+        nodes.push(NodeDefinition::new(3, "output", &self.typ.name, None));
+        nodes
+    }
+
+    fn execute(&self, input: Vec<Register>, block_id: u32) -> Result<Vec<Register>> {
+        let mut values = vec![];
+
+        for (i, (_, typ)) in self.typ.get_fields().enumerate() {
+            let node_id = (i as u32 * 2) + 2;
+
+            let value = input.get(i).ok_or(Error::MissingRegisterValue {
+                block_id,
+                node_id,
+                block_type_id: self.id,
+            })?;
+
+            if value.value.get_name() != typ {
+                return Err(Error::TypeMismatch {
+                    block_id,
+                    node_id,
+                    block_type_id: self.id,
+                    expected: typ.to_string(),
+                    found: value.value.get_name().to_string(),
+                });
+            }
+
+            values.push(value.value.duplicate());
+        }
+
+        Ok(vec![Register {
+            block_id,
+            node_id: 3,
+            value: Box::new(Record {
+                typ: self.typ.clone(),
+                values,
+            }),
+        }])
+    }
+}
+
+/// Splits a record into the values of its fields
+#[derive(Debug)]
+pub struct SplitRecord {
+    id: u32,
+    name: String,
+    typ: Arc<RecordType>,
+}
+
+impl ExecutionBlock for SplitRecord {
+    fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_type(&self) -> ExecutionBlockType {
+        ExecutionBlockType::Static
+    }
+
+    fn get_nodes(&self) -> Vec<NodeDefinition<'_>> {
+        let mut nodes = vec![NodeDefinition::new(2, "input", &self.typ.name, None)];
+
+        nodes.extend(
+            self.typ
+                .get_fields()
+                .enumerate()
+                .map(|(i, (n, t))| NodeDefinition::new((i as u32 * 2) + 3, "output", t, Some(n))),
+        );
+
+        nodes
+    }
+
+    fn execute(&self, input: Vec<Register>, block_id: u32) -> Result<Vec<Register>> {
+        let value = input.first().ok_or(Error::MissingRegisterValue {
+            block_id,
+            node_id: 2,
+            block_type_id: self.id,
+        })?;
+
+        let record = value
+            .value
+            .downcast_ref::<Record>()
+            .filter(|r| r.typ.name == self.typ.name)
+            .ok_or_else(|| Error::TypeMismatch {
+                block_id,
+                node_id: 2,
+                block_type_id: self.id,
+                expected: self.typ.name.to_string(),
+                found: value.value.get_name().to_string(),
+            })?;
+
+        Ok(record
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| Register {
+                block_id,
+                node_id: (i as u32 * 2) + 3,
+                value: v.duplicate(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::execute_block;
+    use crate::types::ExecutionType;
+    use crate::Logic;
+    use serde_json::json;
+
+    fn customer() -> serde_json::Value {
+        json!({
+            "id": 90_000,
+            "name": "Customer",
+            "fields": [
+                { "name": "name", "type": "String" },
+                { "name": "age", "type": "Integer" },
+            ],
+        })
+    }
+
+    #[test]
+    fn create_and_split() {
+        let mut logic = Logic::default();
+        logic.add_record_type(customer()).unwrap();
+
+        let create = logic.get_block(90_000).unwrap();
+        let split = logic.get_block(90_001).unwrap();
+        assert_eq!(create.get_name(), "CreateCustomer");
+        assert_eq!(split.get_name(), "SplitCustomer");
+
+        let inputs: Vec<Box<dyn ExecutionType>> =
+            vec![Box::new(String::from("Ann")), Box::new(42i64)];
+        let record = execute_block(create, inputs).unwrap();
+        assert_eq!(record, vec![json!({ "name": "Ann", "age": 42 })]);
+
+        let value = logic
+            .type_from_json("Customer".to_string(), record[0].clone())
+            .unwrap();
+        assert_eq!(
+            execute_block(split, vec![value]).unwrap(),
+            vec![json!("Ann"), json!(42)]
+        );
+
+        // the fields need to be given in their types
+        let inputs: Vec<Box<dyn ExecutionType>> = vec![Box::new(42i64), Box::new(42i64)];
+        assert!(execute_block(create, inputs).is_err());
+    }
+
+    #[test]
+    fn records_from_json() {
+        let mut logic = Logic::default();
+        logic.add_record_type(customer()).unwrap();
+        let record = |json| logic.type_from_json("Customer".to_string(), json);
+
+        let value = record(json!(r#"{ "name": "Ann", "age": "42" }"#)).unwrap();
+        assert_eq!(value.to_json(), json!({ "name": "Ann", "age": 42 }));
+
+        assert!(record(json!({ "name": "Ann" })).is_err());
+        assert!(record(json!({ "name": "Ann", "age": null })).is_err());
+        assert!(record(json!({ "name": "Ann", "age": "old" })).is_err());
+        assert!(record(json!(["Ann", 42])).is_err());
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        let mut logic = Logic::default();
+        let mut add = |change: &dyn Fn(&mut serde_json::Value)| {
+            let mut schema = customer();
+            change(&mut schema);
+            logic.add_record_type(schema)
+        };
+
+        assert!(add(&|s| s["fields"][1]["name"] = json!("name")).is_err());
+        assert!(add(&|s| s["fields"][1]["type"] = json!("Age")).is_err());
+        assert!(add(&|s| s["id"] = json!(u32::MAX)).is_err());
+        assert!(add(&|s| s["id"] = json!(60_001)).is_err());
+        assert!(add(&|s| s["name"] = json!("Integer")).is_err());
+
+        // nothing was registered by the rejected schemas
+        assert!(add(&|_| {}).is_ok());
+        assert!(add(&|s| s["id"] = json!(91_000)).is_err());
+    }
+}
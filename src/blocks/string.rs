@@ -1,22 +1,24 @@
 use bme_macro::ExecutionBlockHelper;
 use crate::blocks::integer::Integer;
+use crate::error::Result;
 
 impl crate::types::ExecutionType for String {
     fn get_name(&self) -> &'static str {
         "String"
     }
 
-    fn from_json(&self, json: serde_json::Value) -> Box<crate::types::ExecutionType> {
-        let s = serde_json::from_value::<String>(json);
-
-        if let Ok(s) = s {
-            return Box::new(s);
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn crate::types::ExecutionType>> {
+        match serde_json::from_value::<String>(json.clone()) {
+            Ok(s) => Ok(Box::new(s)),
+            Err(_) => Err(format!("{} is not a valid String", json).into()),
         }
+    }
 
-        Box::new(String::new())
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self)
     }
 
-    fn duplicate(&self) -> Box<crate::types::ExecutionType> {
+    fn duplicate(&self) -> Box<dyn crate::types::ExecutionType> {
         Box::new(self.clone())
     }
 
@@ -33,13 +35,19 @@ impl crate::types::ExecutionType for String {
     }
 }
 
-pub fn add_blocks(logic: &mut crate::Logic) {
-    logic.add_block(Box::new(StringAdd {}));
-    logic.add_block(Box::new(StringLength {}));
-    logic.add_block(Box::new(StringTrim {}));
-    logic.add_block(Box::new(StringToLowercase {}));
-    logic.add_block(Box::new(StringToUppercase {}));
-    logic.add_block(Box::new(StringInsert {}));
+pub fn add_blocks(logic: &mut crate::Logic) -> Result<()> {
+    logic.add_block(Box::new(StringAdd {}))?;
+    logic.add_block(Box::new(StringLength {}))?;
+    logic.add_block(Box::new(StringTrim {}))?;
+    logic.add_block(Box::new(StringToLowercase {}))?;
+    logic.add_block(Box::new(StringToUppercase {}))?;
+    logic.add_block(Box::new(StringInsert {}))?;
+    logic.add_block(Box::new(StringEqual {}))?;
+    logic.add_block(Box::new(StringNotEqual {}))?;
+    logic.add_block(Box::new(StringLess {}))?;
+    logic.add_block(Box::new(StringGreater {}))?;
+
+    Ok(())
 }
 
 ExecutionBlockHelper!(
@@ -103,15 +111,59 @@ ExecutionBlockHelper!(
     typ: Static,
     path: crate,
 
-    fn execute(inp1: String, index: Integer, inp2: String) -> (String) {
-        let mut inp1 = inp1;
+    fn execute(text: String, index: Integer, insert: String) -> (String) {
+        let mut text = text;
         let mut index = index as usize;
         
-        if(index > inp1.len()) {
-            index = inp1.len();
+        if(index > text.len()) {
+            index = text.len();
         }
 
-        inp1.insert_str(index, &inp2);
-        (inp1)
+        text.insert_str(index, &insert);
+        (text)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 68007,
+    name: StringEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: String, inp2: String) -> (Boolean) {
+        (inp1 == inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 68008,
+    name: StringNotEqual,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: String, inp2: String) -> (Boolean) {
+        (inp1 != inp2)
     }
-);
\ No newline at end of file
+);
+
+ExecutionBlockHelper!(
+    id: 68009,
+    name: StringLess,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: String, inp2: String) -> (Boolean) {
+        (inp1 < inp2)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 68010,
+    name: StringGreater,
+    typ: Static,
+    path: crate,
+
+    fn execute(inp1: String, inp2: String) -> (Boolean) {
+        (inp1 > inp2)
+    }
+);
@@ -1,66 +1,356 @@
+use crate::validation::Violation;
+
 pub type Result<T> = std::result::Result<T, crate::error::Error>;
 
+/// Errors which can occure while analyzing or executing a model.
+///
+/// Errors which are related to a specific block carry the id of the block
+/// within the model (`block_id`), the id of the node (`node_id`) and the
+/// id of the block type (`block_type_id`), so the failing block can be
+/// highlighted within the visual model.
 #[derive(Debug)]
-pub struct Error {
-    error: std::result::Result<String, Box<dyn std::error::Error>>,
+pub enum Error {
+    /// The code of the model could not be parsed
+    ParseError(serde_json::Error),
+    /// The code of the model violates one or more rules
+    ValidationError(Vec<Violation>),
+    /// The model has no start block
+    NoStartBlock,
+    /// The model has no block with the given id
+    UnknownBlock { block_id: u32 },
+    /// The logic has no block type with the given id
+    UnknownBlockType { block_id: u32, block_type_id: u32 },
+    /// The logic has no execution type with the given name
+    UnknownConnectionType {
+        block_id: u32,
+        node_id: u32,
+        block_type_id: u32,
+        connection_type: String,
+    },
+    /// The value for a node was not available in the register
+    MissingRegisterValue {
+        block_id: u32,
+        node_id: u32,
+        block_type_id: u32,
+    },
+    /// A block returned a value for a node which is no output of the block,
+    /// like an execution output it doesn't have
+    InvalidNextNode {
+        block_id: u32,
+        node_id: u32,
+        block_type_id: u32,
+    },
+    /// The value for an input doesn't have the expected execution type
+    TypeMismatch {
+        block_id: u32,
+        node_id: u32,
+        block_type_id: u32,
+        expected: String,
+        found: String,
+    },
+    /// The execution of a block panicked
+    BlockPanicked {
+        block_id: u32,
+        block_type_id: u32,
+        message: String,
+    },
+    /// The execution of a block returned an error
+    BlockFailed {
+        block_id: u32,
+        block_type_id: u32,
+        source: Box<Error>,
+    },
+    /// A loop ran its body, or a run executed blocks, more often than the
+    /// context allows
+    IterationLimit {
+        block_id: u32,
+        block_type_id: u32,
+        limit: usize,
+    },
+    /// An integer operation overflowed the range of its type
+    Overflow,
+    /// A number was divided by zero
+    DivisionByZero,
+    /// Generic error without any further context
+    Message(String),
 }
 
 impl Error {
-    pub fn new(error: Box<dyn std::error::Error>) -> Error {
-        Error { error: Err(error) }
+    /// Returns the id of the block within the model which caused the error
+    pub fn block_id(&self) -> Option<u32> {
+        match self {
+            Error::UnknownBlock { block_id }
+            | Error::UnknownBlockType { block_id, .. }
+            | Error::UnknownConnectionType { block_id, .. }
+            | Error::MissingRegisterValue { block_id, .. }
+            | Error::InvalidNextNode { block_id, .. }
+            | Error::TypeMismatch { block_id, .. }
+            | Error::BlockPanicked { block_id, .. }
+            | Error::BlockFailed { block_id, .. }
+            | Error::IterationLimit { block_id, .. } => Some(*block_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the id of the node which caused the error
+    pub fn node_id(&self) -> Option<u32> {
+        match self {
+            Error::UnknownConnectionType { node_id, .. }
+            | Error::MissingRegisterValue { node_id, .. }
+            | Error::InvalidNextNode { node_id, .. }
+            | Error::TypeMismatch { node_id, .. } => Some(*node_id),
+            Error::BlockFailed { source, .. } => source.node_id(),
+            _ => None,
+        }
+    }
+
+    /// Returns the id of the block type which caused the error
+    pub fn block_type_id(&self) -> Option<u32> {
+        match self {
+            Error::UnknownBlockType { block_type_id, .. }
+            | Error::UnknownConnectionType { block_type_id, .. }
+            | Error::MissingRegisterValue { block_type_id, .. }
+            | Error::InvalidNextNode { block_type_id, .. }
+            | Error::TypeMismatch { block_type_id, .. }
+            | Error::BlockPanicked { block_type_id, .. }
+            | Error::BlockFailed { block_type_id, .. }
+            | Error::IterationLimit { block_type_id, .. } => Some(*block_type_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the violations when the error was raised by the validation of the code
+    pub fn violations(&self) -> Option<&[Violation]> {
+        match self {
+            Error::ValidationError(v) => Some(v.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Tags an error returned by the execution of a block with the block.
+    /// Errors which already name a block are returned unchanged.
+    pub fn in_block(self, block_id: u32, block_type_id: u32) -> Error {
+        if self.block_id().is_some() {
+            return self;
+        }
+
+        Error::BlockFailed {
+            block_id,
+            block_type_id,
+            source: Box::new(self),
+        }
     }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match &self.error {
-            Ok(s) => write!(f, "{}", s),
-            Err(e) => e.fmt(f),
+        match self {
+            Error::ParseError(e) => write!(f, "The code could not be parsed: {}", e),
+            Error::ValidationError(violations) => {
+                write!(f, "The code is not valid:")?;
+
+                for v in violations {
+                    write!(f, "\n - {}", v)?;
+                }
+
+                Ok(())
+            }
+            Error::NoStartBlock => write!(f, "No start block available"),
+            Error::UnknownBlock { block_id } => {
+                write!(f, "No block with the id {} available", block_id)
+            }
+            Error::UnknownBlockType {
+                block_id,
+                block_type_id,
+            } => write!(
+                f,
+                "Block {}: The block type {} is not available",
+                block_id, block_type_id
+            ),
+            Error::UnknownConnectionType {
+                block_id,
+                node_id,
+                connection_type,
+                ..
+            } => write!(
+                f,
+                "Block {}, node {}: The execution type {} is not available",
+                block_id, node_id, connection_type
+            ),
+            Error::MissingRegisterValue {
+                block_id, node_id, ..
+            } => write!(
+                f,
+                "Block {}, node {}: Value not available in register",
+                block_id, node_id
+            ),
+            Error::InvalidNextNode {
+                block_id,
+                node_id,
+                block_type_id,
+            } => write!(
+                f,
+                "Block {}, node {}: The block type {} has no output with this node",
+                block_id, node_id, block_type_id
+            ),
+            Error::TypeMismatch {
+                block_id,
+                node_id,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "Block {}, node {}: Expected a value of type {} but found {}",
+                block_id, node_id, expected, found
+            ),
+            Error::BlockPanicked {
+                block_id, message, ..
+            } => write!(f, "Block {}: The execution panicked: {}", block_id, message),
+            Error::BlockFailed {
+                block_id, source, ..
+            } => write!(f, "Block {}: {}", block_id, source),
+            Error::IterationLimit {
+                block_id, limit, ..
+            } => write!(
+                f,
+                "Block {}: The execution exceeded the limit of {} iterations",
+                block_id, limit
+            ),
+            Error::Overflow => write!(f, "The result is out of range"),
+            Error::DivisionByZero => write!(f, "Division by zero"),
+            Error::Message(s) => write!(f, "{}", s),
         }
     }
 }
 
 impl std::error::Error for Error {
-    fn description(&self) -> &str {
-        match &self.error {
-            Ok(s) => s,
-            Err(e) => e.description(),
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ParseError(e) => Some(e),
+            Error::BlockFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
         }
     }
+}
+
+/// Conversion of the errors returned by block functions, used by the
+/// code generated for fallible blocks.
+///
+/// An `Error` of this crate is kept as it is, any other error which
+/// implements `Display` becomes an `Error::Message`.
+#[doc(hidden)]
+pub mod convert {
+    use super::Error;
+
+    pub struct Wrap<T>(pub T);
 
-    fn cause(&self) -> Option<&std::error::Error> {
-        None
+    pub trait ViaError {
+        fn into_error(self) -> Error;
+    }
+
+    impl ViaError for Wrap<Error> {
+        fn into_error(self) -> Error {
+            self.0
+        }
+    }
+
+    pub trait ViaDisplay {
+        fn into_error(self) -> Error;
+    }
+
+    impl<T: std::fmt::Display> ViaDisplay for &Wrap<T> {
+        fn into_error(self) -> Error {
+            Error::Message(self.0.to_string())
+        }
     }
 }
 
 // immplement error from string
 impl From<&str> for Error {
     fn from(err: &str) -> Self {
-        Error {
-            error: Ok(err.to_string()),
-        }
+        Error::Message(err.to_string())
     }
 }
 
 // immplement error from string
 impl From<String> for Error {
     fn from(err: String) -> Self {
-        Error {
-            error: Ok(err),
-        }
+        Error::Message(err)
     }
 }
 
 // immplement error for system time
 impl From<std::time::SystemTimeError> for Error {
     fn from(err: std::time::SystemTimeError) -> Self {
-        Error::new(Box::new(err))
+        Error::Message(err.to_string())
     }
 }
 
-// immplement error for system time
+// immplement error for serde json
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
-        Error::new(Box::new(err))
+        Error::ParseError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_block_tags_errors_without_a_block() {
+        for error in [
+            Error::Overflow,
+            Error::NoStartBlock,
+            Error::Message("failed".into()),
+        ] {
+            match error.in_block(3, 64_001) {
+                Error::BlockFailed {
+                    block_id: 3,
+                    block_type_id: 64_001,
+                    ..
+                } => {}
+                e => panic!("unexpected error {:?}", e),
+            }
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn in_block_keeps_errors_with_a_block() {
+        let error = Error::TypeMismatch {
+            block_id: 5,
+            node_id: 2,
+            block_type_id: 60_001,
+            expected: "String".into(),
+            found: "Integer".into(),
+        };
+
+        match error.in_block(3, 64_001) {
+            Error::TypeMismatch { block_id: 5, .. } => {}
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn context_of_wrapped_errors() {
+        let error = Error::MissingRegisterValue {
+            block_id: 5,
+            node_id: 4,
+            block_type_id: 60_001,
+        };
+        let error = Error::BlockFailed {
+            block_id: 5,
+            block_type_id: 60_001,
+            source: Box::new(error),
+        };
+
+        assert_eq!(error.block_id(), Some(5));
+        assert_eq!(error.node_id(), Some(4));
+        assert_eq!(error.block_type_id(), Some(60_001));
+        assert_eq!(
+            Error::DivisionByZero.in_block(7, 64_004).to_string(),
+            "Block 7: Division by zero"
+        );
+    }
+}
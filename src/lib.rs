@@ -1,23 +1,53 @@
 pub mod blocks;
 pub mod error;
+mod plan;
 pub mod types;
+pub mod validation;
+
+#[cfg(test)]
+mod test_util;
 
 pub use bme_macro::*;
+pub use plan::{Context, DEFAULT_MAX_ITERATIONS, DEFAULT_MAX_STEPS};
+
+// used by the code generated by `#[derive(ExecutionType)]`
+#[doc(hidden)]
+pub use serde_json;
 
-use error::Result;
+use error::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Execution Block trait which needs to be implemented to generate
 /// a new execution block, which can be used within the rule engine.
 ///
 /// There is the makro which allows the creating of blocks in a convienient way.
-pub trait ExecutionBlock: std::fmt::Debug {
+pub trait ExecutionBlock: std::fmt::Debug + Send + Sync {
     fn get_id(&self) -> u32;
-    fn get_name(&self) -> &'static str;
+    fn get_name(&self) -> &str;
     fn get_type(&self) -> ExecutionBlockType;
+
+    /// Description of the block to show in the editor
+    fn get_description(&self) -> Option<&'static str> {
+        None
+    }
+
     fn execute(&self, input: Vec<Register>, block_id: u32) -> Result<Vec<Register>>;
 
+    /// Called for loop blocks instead of `execute`, before every run of the
+    /// loop body. The inputs are read again for every call.
+    fn iterate(
+        &self,
+        _input: Vec<Register>,
+        _block_id: u32,
+        _state: &mut LoopState,
+    ) -> Result<LoopStep> {
+        Err("The block is not a loop block".into())
+    }
+
+    /// Connection types of the data inputs. Blocks which define their
+    /// nodes by overriding `get_nodes` don't need to give them.
     fn get_inputs(&self) -> &'static [&'static str] {
         &[]
     }
@@ -26,24 +56,73 @@ pub trait ExecutionBlock: std::fmt::Debug {
         &[]
     }
 
-    fn get_json(&self) -> serde_json::Value {
-        let mut nodes: Vec<serde_json::Value> = vec![];
+    /// Names of the data inputs, in the same order as `get_inputs`.
+    /// Inputs without a pin are shown unnamed.
+    fn get_input_pins(&self) -> &'static [Pin] {
+        &[]
+    }
+
+    /// Names of the data outputs, in the same order as `get_outputs`.
+    /// Outputs without a pin are shown unnamed.
+    fn get_output_pins(&self) -> &'static [Pin] {
+        &[]
+    }
+
+    /// Names of the execution outputs of a normal or loop block. The block
+    /// selects the output to run next by returning a `types::Execution` value
+    /// for its node, otherwise the first one is used. Loop blocks run their
+    /// body with the first and continue with the second output when completed.
+    fn get_next_outputs(&self) -> &'static [&'static str] {
+        &["Next"]
+    }
+
+    /// Number of inputs at the end of a loop block which are fed back from
+    /// the loop body, like the mapped value of a map block. They are only
+    /// passed to `iterate` after the first run of the loop body.
+    fn get_feedback_inputs(&self) -> usize {
+        0
+    }
+
+    /// Loop blocks which keep the inputs of their first call in the
+    /// `LoopState`, like the list of a map block, only get the inputs fed
+    /// back from the loop body on later calls.
+    fn keeps_inputs(&self) -> bool {
+        false
+    }
+
+    /// Static blocks which return the same outputs for the same inputs are
+    /// only executed once per run, until one of the blocks they depend on
+    /// changes. Blocks like random numbers or clocks need to return false.
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Returns the nodes of this block in the order they are presented
+    /// to the editor. The node ids are the ones used within the code.
+    fn get_nodes(&self) -> Vec<NodeDefinition<'_>> {
+        let mut nodes: Vec<NodeDefinition> = vec![];
 
         // based on the node type we generate default inputs / outputs
         match self.get_type() {
             ExecutionBlockType::Start => {
-                nodes.push(serde_json::json!({ "id": 0, "io": "output", "type": "Execution", "name": "Next" }));
+                nodes.push(NodeDefinition::new(0, "output", "Execution", Some("Next")));
+            }
+            ExecutionBlockType::Normal | ExecutionBlockType::Loop => {
+                nodes.push(NodeDefinition::new(0, "input", "Execution", Some("Run")));
+
+                if let Some(n) = self.get_next_outputs().first() {
+                    nodes.push(NodeDefinition::new(1, "output", "Execution", Some(n)));
+                }
             }
-            ExecutionBlockType::Normal => {
-                nodes.push(serde_json::json!({ "id": 0, "io": "input", "type": "Execution", "name": "Run" }));
-                nodes.push(serde_json::json!({ "id": 1, "io": "output", "type": "Execution", "name": "Next" }));
+            ExecutionBlockType::End => {
+                nodes.push(NodeDefinition::new(0, "input", "Execution", Some("Run")));
             }
             ExecutionBlockType::Static | ExecutionBlockType::Comment => {}
         };
 
         // add the other defined input & outputs
-        let mut inp = self.get_inputs().iter();
-        let mut out = self.get_outputs().iter();
+        let mut inp = self.get_inputs().iter().enumerate();
+        let mut out = self.get_outputs().iter().enumerate();
         let mut index = 2;
 
         loop {
@@ -52,29 +131,143 @@ pub trait ExecutionBlock: std::fmt::Debug {
             let o = out.next();
 
             // try to add the next input
-            if let Some(n) = i {
-                nodes.push(serde_json::json!({ "id": index, "io": "input", "type": n }));
+            if let Some((k, n)) = i {
+                let node = NodeDefinition::new(index, "input", n, None);
+                nodes.push(match self.get_input_pins().get(k) {
+                    Some(pin) => node.with_pin(pin),
+                    None => node,
+                });
             }
 
             // try to add the next output
-            if let Some(n) = o {
-                nodes.push(serde_json::json!({ "id": index+1, "io": "output", "type": n }));
+            if let Some((k, n)) = o {
+                let node = NodeDefinition::new(index + 1, "output", n, None);
+                nodes.push(match self.get_output_pins().get(k) {
+                    Some(pin) => node.with_pin(pin),
+                    None => node,
+                });
             }
 
-            index += 2;
-
             // when input & output are completly loop over, end
             if i.is_none() && o.is_none() {
                 break;
             }
+
+            index += 2;
         }
 
-        serde_json::json!({
+        // further execution outputs are added behind all other nodes
+        if let ExecutionBlockType::Normal | ExecutionBlockType::Loop = self.get_type() {
+            for n in self.get_next_outputs().iter().skip(1) {
+                nodes.push(NodeDefinition::new(
+                    index + 1,
+                    "output",
+                    "Execution",
+                    Some(n),
+                ));
+                index += 2;
+            }
+        }
+
+        nodes
+    }
+
+    fn get_json(&self) -> serde_json::Value {
+        let nodes = self
+            .get_nodes()
+            .iter()
+            .map(|n| n.get_json())
+            .collect::<Vec<serde_json::Value>>();
+
+        let mut json = serde_json::json!({
             "id": self.get_id(),
             "name": self.get_name(),
             "typ": self.get_type(),
             "nodes": nodes,
-        })
+        });
+
+        if let Some(description) = self.get_description() {
+            json["description"] = serde_json::json!(description);
+        }
+
+        json
+    }
+}
+
+/// Definition of a single node (input or output) of an execution block
+#[derive(Debug, PartialEq, Clone)]
+pub struct NodeDefinition<'a> {
+    pub id: u32,
+    pub io: &'static str,
+    pub typ: &'a str,
+    pub name: Option<&'a str>,
+    pub label: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+impl<'a> NodeDefinition<'a> {
+    pub fn new(
+        id: u32,
+        io: &'static str,
+        typ: &'a str,
+        name: Option<&'a str>,
+    ) -> NodeDefinition<'a> {
+        NodeDefinition {
+            id,
+            io,
+            typ,
+            name,
+            label: None,
+            description: None,
+        }
+    }
+
+    /// Takes the name, label and description of the node from a pin.
+    /// A pin with an empty name leaves the node unnamed.
+    pub fn with_pin(mut self, pin: &Pin) -> NodeDefinition<'a> {
+        if !pin.name.is_empty() {
+            self.name = Some(pin.name);
+        }
+        self.label = pin.label;
+        self.description = pin.description;
+        self
+    }
+
+    pub fn get_json(&self) -> serde_json::Value {
+        let mut json = serde_json::json!({ "id": self.id, "io": self.io, "type": self.typ });
+
+        if let Some(name) = self.name {
+            json["name"] = serde_json::json!(name);
+        }
+
+        if let Some(label) = self.label {
+            json["label"] = serde_json::json!(label);
+        }
+
+        if let Some(description) = self.description {
+            json["description"] = serde_json::json!(description);
+        }
+
+        json
+    }
+}
+
+/// Name of a data input or output of an execution block, with an optional
+/// label and description to show in the editor
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Pin {
+    pub name: &'static str,
+    pub label: Option<&'static str>,
+    pub description: Option<&'static str>,
+}
+
+impl Pin {
+    pub const fn new(name: &'static str) -> Pin {
+        Pin {
+            name,
+            label: None,
+            description: None,
+        }
     }
 }
 
@@ -83,68 +276,178 @@ pub trait ExecutionBlock: std::fmt::Debug {
 pub struct Register {
     pub block_id: u32,
     pub node_id: u32,
-    pub value: Box<types::ExecutionType>,
+    pub value: Box<dyn types::ExecutionType>,
+}
+
+/// State of a running loop block, kept by the executer between the runs of the loop body
+#[derive(Debug, Default)]
+pub struct LoopState {
+    /// Number of times the loop body was run so far
+    pub iteration: usize,
+    /// Values collected by the loop block while the loop body runs
+    pub values: Vec<Box<dyn types::ExecutionType>>,
+    /// Values kept by the loop block from its first call
+    pub inputs: Vec<Box<dyn types::ExecutionType>>,
+}
+
+/// Result of a single iteration of a loop block
+#[derive(Debug)]
+pub enum LoopStep<T = Vec<Register>> {
+    /// Run the loop body once more with the given outputs
+    Body(T),
+    /// The loop is finished, continue after the loop with the given outputs
+    Completed(T),
 }
 
 /// Enum for the different types of a Execution blocks
+///
+/// The outputs of the `Start` block are filled with the inputs given to the
+/// execution and the inputs of the `End` block are returned as the result of
+/// the execution. Both are never executed themselves.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum ExecutionBlockType {
     Start,
+    End,
     Static,
     Normal,
-    Comment
+    Loop,
+    Comment,
+}
+
+/// Execution types every logic knows, also the ones a list can hold
+pub(crate) fn built_in_types() -> Vec<Box<dyn types::ExecutionType>> {
+    vec![
+        Box::new(types::Execution::new()),
+        Box::new(String::new()),
+        Box::new(0i64),
+        Box::new(0.0f64),
+        Box::new(false),
+        Box::new(crate::blocks::float_vec3::FloatVector3::new(0.0, 0.0, 0.0)),
+        Box::new(crate::blocks::list::List::default()),
+        Box::new(crate::blocks::decimal::Decimal::default()),
+        Box::new(crate::blocks::date::Date::default()),
+        Box::new(crate::blocks::date::DateTime::default()),
+        Box::new(crate::blocks::date::Duration::default()),
+    ]
 }
 
 /// Logic which collects the execution blocks as well as the nodes
 pub struct Logic {
-    blocks: Vec<Box<ExecutionBlock>>,
-    types: Vec<Box<types::ExecutionType>>,
+    blocks: Vec<Arc<dyn ExecutionBlock>>,
+    index: HashMap<u32, usize>,
+    types: Vec<Box<dyn types::ExecutionType>>,
 }
 
 impl Logic {
     pub fn empty() -> Logic {
         Logic {
             blocks: vec![],
-            types: vec![
-                Box::new(types::Execution::new()),
-                Box::new(String::new()),
-                Box::new(0i64),
-                Box::new(0.0f64),
-                Box::new(crate::blocks::float_vec3::FloatVector3::new(0.0, 0.0, 0.0)),
-            ],
+            index: HashMap::new(),
+            types: built_in_types(),
         }
     }
 
-    pub fn add_block(&mut self, block: Box<ExecutionBlock>) {
-        self.blocks.push(block);
+    /// Adds a block to the logic. Every block needs its own id, a block
+    /// with an id which is already used is rejected.
+    pub fn add_block(&mut self, block: Box<dyn ExecutionBlock>) -> Result<()> {
+        let id = block.get_id();
+
+        if let Some(other) = self.get_block(id) {
+            return Err(format!(
+                "The block id {} of {} is already used by {}",
+                id,
+                block.get_name(),
+                other.get_name()
+            )
+            .into());
+        }
+
+        self.index.insert(id, self.blocks.len());
+        self.blocks.push(Arc::from(block));
+        Ok(())
+    }
+
+    /// Removes the block with the given id, returns false when there was none
+    pub fn remove_block(&mut self, id: u32) -> bool {
+        let len = self.blocks.len();
+        self.blocks.retain(|b| b.get_id() != id);
+
+        self.index = self
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.get_id(), i))
+            .collect();
+
+        len != self.blocks.len()
     }
 
-    pub fn get_block(&self, id: u32) -> Option<&Box<ExecutionBlock>> {
-        self.blocks.iter().find(|b| b.get_id() == id)
+    pub fn get_block(&self, id: u32) -> Option<&dyn ExecutionBlock> {
+        self.get_shared_block(id).map(|b| b.as_ref())
     }
 
-    pub fn get_blocks_by_type(&self, typ: ExecutionBlockType) -> Vec<&Box<ExecutionBlock>> {
+    fn get_shared_block(&self, id: u32) -> Option<&Arc<dyn ExecutionBlock>> {
+        self.index.get(&id).map(|&i| &self.blocks[i])
+    }
+
+    pub fn get_blocks_by_type(&self, typ: ExecutionBlockType) -> Vec<&dyn ExecutionBlock> {
         self.blocks
             .iter()
             .filter(|b| b.get_type() == typ)
-            .collect::<Vec<&Box<ExecutionBlock>>>()
+            .map(|b| b.as_ref())
+            .collect::<Vec<&dyn ExecutionBlock>>()
     }
 
-    pub fn add_connection_type(&mut self, typ: Box<types::ExecutionType>) {
+    pub fn add_connection_type(&mut self, typ: Box<dyn types::ExecutionType>) {
         self.types.push(typ);
     }
 
+    pub fn has_connection_type(&self, name: &str) -> bool {
+        self.types.iter().any(|t| t.get_name() == name)
+    }
+
+    /// Declares a record type from a json schema, together with the blocks
+    /// to create and split it, see `blocks::record::RecordSchema`
+    pub fn add_record_type(&mut self, schema: serde_json::Value) -> Result<()> {
+        let schema = serde_json::from_value(schema)?;
+        blocks::record::add_record_type(self, schema)
+    }
+
+    /// Removes the execution type with the given name, returns false when there was none
+    pub fn remove_connection_type(&mut self, name: &str) -> bool {
+        let len = self.types.len();
+        self.types.retain(|t| t.get_name() != name);
+        len != self.types.len()
+    }
+
+    /// Converts a json value, like the value of an input without a
+    /// connection, into a value of the execution type with the given name.
+    ///
+    /// No type has a default value: `null`, which the editor sends for an
+    /// input left empty, is rejected for every type, so an input which was
+    /// forgotten doesn't quietly become `0`, `""` or `1970-01-01`.
     pub fn type_from_json(
         &self,
         typ: String,
         json: serde_json::Value,
-    ) -> Result<Box<types::ExecutionType>> {
+    ) -> Result<Box<dyn types::ExecutionType>> {
         let res = self
-            .types
-            .iter()
-            .find(|t| t.get_name() == typ)
+            .get_connection_type(&typ)
             .ok_or("No matching Execution Type found")?;
-        Ok(res.from_json(json))
+
+        if json.is_null() {
+            return Err(format!("A value of the type {} is required", typ).into());
+        }
+
+        res.from_json(json)
+    }
+
+    /// Returns the value the logic keeps for the execution type with the given name
+    pub(crate) fn get_connection_type(&self, name: &str) -> Option<&dyn types::ExecutionType> {
+        self.types
+            .iter()
+            .find(|t| t.get_name() == name)
+            .map(|t| t.as_ref())
     }
 
     pub fn get_connection_json(&self) -> Vec<serde_json::Value> {
@@ -154,9 +457,9 @@ impl Logic {
             cons.push(serde_json::json!({
                 "type": c.get_name(),
                 "color": c.get_color(),
-                "valueEdit": if c.get_edit_default() == None {false} else {true},
-                "valueDefault": if let Some(s) = c.get_edit_default() {s} else {""},
-                "valueCheck": "",
+                "valueEdit": c.get_edit_default().is_some(),
+                "valueDefault": c.get_edit_default().unwrap_or_default(),
+                "valueCheck": c.get_value_check().unwrap_or_default(),
                 "multiOutput": c.get_multi_output(),
             }));
         }
@@ -190,217 +493,143 @@ impl Logic {
 impl Default for Logic {
     fn default() -> Self {
         let mut logic = Logic::empty();
-        blocks::add_blocks(&mut logic);
+        blocks::add_blocks(&mut logic).expect("The ids of the built-in blocks are unique");
         logic
     }
 }
 
-pub struct Executer {
-    pub logic: Logic,
-    raw_code: String,
-    code: Vec<Block>,
-    code_ok: bool,
-    register: HashMap<(u32, u32), Box<types::ExecutionType>>,
+/// Model which is parsed, validated and compiled against a logic.
+///
+/// The model is immutable and can be shared between threads, every
+/// execution keeps its state within its own `Context`.
+pub struct Model {
+    plan: plan::Plan,
 }
 
-impl Executer {
-    pub fn new(code: String) -> Executer {
-        Executer {
-            logic: Logic::default(),
-            raw_code: code,
-            code: vec![],
-            code_ok: false,
-            register: HashMap::new(),
+impl Model {
+    /// Parses and validates the code and compiles it into an execution plan
+    pub fn compile(code: &str, logic: &Logic) -> Result<Model> {
+        let code: Vec<Block> = serde_json::from_str(code)?;
+
+        let (violations, literals) = validation::validate(&code, logic);
+        if !violations.is_empty() {
+            return Err(Error::ValidationError(violations));
         }
-    }
 
-    pub fn set_code<S: Into<String>>(&mut self, code: S) {
-        self.raw_code = code.into();
-        self.code_ok = false;
+        Ok(Model {
+            plan: plan::Plan::compile(&code, logic, literals)?,
+        })
     }
 
-    pub fn analyze(&mut self) -> Result<()> {
-        self.code = serde_json::from_str(&self.raw_code)?;
-
-        // todo
-        // - Check for only 1 Block Start Type
-        // - Check for only 1 Start Block
-        // - Check if all connections used in blocks exist
-        // - Check if all connections use in blocks are valid
-        // - Check that there is only one Execution type output per node
-        // - Check if only one input connection per node exist
-
-        self.code_ok = true;
-        Ok(())
+    /// Executes the model with a new context, see `execute_with`
+    pub fn execute(
+        &self,
+        inputs: Vec<Box<dyn types::ExecutionType>>,
+    ) -> Result<Option<Vec<Box<dyn types::ExecutionType>>>> {
+        self.execute_with(&mut Context::new(), inputs)
     }
 
-    pub fn execute(&mut self, inputs: Vec<Box<types::ExecutionType>>) -> Result<()> {
-        if !self.code_ok {
-            self.analyze()?;
-        }
-
-        self.register.clear();
-        let start_block = self.find_start_block()?;
+    /// Executes the model with the given inputs for the start block.
+    ///
+    /// Returns the inputs of the end block which was reached, or none when
+    /// the execution ended without reaching an end block. After a
+    /// failed execution the values computed so far can be read from the
+    /// context with `Context::get_register_json`.
+    pub fn execute_with(
+        &self,
+        context: &mut Context,
+        inputs: Vec<Box<dyn types::ExecutionType>>,
+    ) -> Result<Option<Vec<Box<dyn types::ExecutionType>>>> {
+        self.plan.execute(context, inputs)
+    }
+}
 
-        for (i, v) in inputs.into_iter().enumerate() {
-            let i = ((i * 2) + 3) as u32;
+pub struct Executer {
+    logic: Arc<Logic>,
+    raw_code: String,
+    model: Option<Arc<Model>>,
+    context: Context,
+}
 
-            self.register.insert((start_block.block_id, i), v);
-        }
+impl Executer {
+    pub fn new(code: String) -> Executer {
+        Executer::with_logic(code, Logic::default())
+    }
 
-        // execute and on error debug register
-        if let Err(e) = self.execute_block(start_block.block_id) {
-            log(format!("Register: {:?}", self.register));
-            return Err(e);
+    /// Creates an executer which uses the given logic instead of the default one.
+    ///
+    /// The logic can be passed as `Logic` or as `Arc<Logic>` to share it
+    /// between many executers.
+    pub fn with_logic<L: Into<Arc<Logic>>>(code: String, logic: L) -> Executer {
+        Executer {
+            logic: logic.into(),
+            raw_code: code,
+            model: None,
+            context: Context::new(),
         }
-
-        Ok(())
     }
 
-    fn find_start_block(&self) -> Result<Block> {
-        let blocks = self.logic.get_blocks_by_type(ExecutionBlockType::Start);
-        let start_block = blocks
-            .get(0)
-            .ok_or("No start blocks defined in the logic")?;
-
-        let block = self
-            .code
-            .iter()
-            .find(|&b| b.block_type_id == start_block.get_id())
-            .ok_or("No start Block available")?;
+    pub fn get_logic(&self) -> &Arc<Logic> {
+        &self.logic
+    }
 
-        Ok(block.clone())
+    pub fn set_logic<L: Into<Arc<Logic>>>(&mut self, logic: L) {
+        self.logic = logic.into();
+        self.model = None;
     }
 
-    fn execute_block(&mut self, next_block_id: u32) -> Result<()> {
-        // handle the input of the node and execute the node
-        let reg = self.exec_inputs(next_block_id)?;
+    pub fn set_code<S: Into<String>>(&mut self, code: S) {
+        self.raw_code = code.into();
+        self.model = None;
+    }
 
-        // insert the result into the register
-        for r in reg {
-            self.register.insert((r.block_id, r.node_id), r.value);
-        }
+    /// Returns the context of the last execution, see `Context::get_register_json`
+    pub fn get_context(&self) -> &Context {
+        &self.context
+    }
 
-        // get the block for the block id
-        let block = self
-            .code
-            .iter()
-            .find(|&b| b.block_id == next_block_id)
-            .ok_or("No Block with the given id avilable")?;
+    /// Sets the number of runs of a loop body after which a loop fails,
+    /// see `Context::set_max_iterations`
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.context.set_max_iterations(max_iterations);
+    }
 
-        // get the next node if available
-        if let Some(next_node) = block
-            .nodes
-            .iter()
-            .find(|&n| n.node_type == "output" && n.connection_type == "Execution")
-        {
-            // get the next block id if avilable
-            if let Some(next_block_id) = next_node.connections.get(0) {
-                #[allow(mutable_borrow_reservation_conflict)]
-                self.execute_block(next_block_id.end_block)?;
-            }
-        }
+    /// Sets the number of blocks a run may execute before it fails,
+    /// see `Context::set_max_steps`
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.context.set_max_steps(max_steps);
+    }
 
-        // When this point is reached, the programm has ended sucessfully
+    /// Parses and validates the code and compiles it into an execution plan
+    pub fn analyze(&mut self) -> Result<()> {
+        self.model = None;
+        self.model = Some(Arc::new(Model::compile(&self.raw_code, &self.logic)?));
         Ok(())
     }
 
-    fn exec_inputs(&self, block_id: u32) -> Result<Vec<Register>> {
-        // get the block for the block id
-        let block = self
-            .code
-            .iter()
-            .find(|&b| b.block_id == block_id)
-            .ok_or("No Block with the given id avilable")?;
-
-        // get all input nodes for this block which are not of type execution
-        let inputs = block
-            .nodes
-            .iter()
-            .filter(|n| n.node_type == "input" && n.connection_type != "Execution")
-            .collect::<Vec<&Node>>();
-
-        let mut results: Vec<Register> = vec![];
-
-        // handle all input nodes
-        for n in inputs {
-            // when another block is connected
-            if n.connections.len() == 1 {
-                let con = n
-                    .connections
-                    .get(0)
-                    .ok_or("There is no connection for the node avialble")?;
-
-                let con_block_type_id = self.code
-                    .iter()
-                    .find(|&b| b.block_id == con.start_block)
-                    .ok_or("No Block with the given id avilable")?
-                    .block_type_id;
-
-                // get the exec_block
-                let exec_block = self
-                    .logic
-                    .get_block(con_block_type_id)
-                    .ok_or("The given Block Type is not avilable")?;
-
-                // based upon the block type we have different executions
-                match exec_block.get_type() {
-                    ExecutionBlockType::Static => {
-                        let values = self.exec_inputs(con.start_block)?;
-
-                        results.push(
-                            values
-                                .into_iter()
-                                .find(|v| v.node_id == con.start_node)
-                                .ok_or("No value available")?,
-                        );
-                    }
-                    ExecutionBlockType::Normal | ExecutionBlockType::Start => {
-                        let value = self
-                            .register
-                            .get(&(con.start_block, con.start_node))
-                            .ok_or("Value not avilable in register")?;
-
-                        results.push(Register {
-                            block_id: con.start_block,
-                            node_id: con.start_node,
-                            value: value.duplicate(),
-                        });
-                    }
-                    ExecutionBlockType::Comment => { 
-                        // This should not happen, comments cant be executed
-                    }
-                };
-
-            // when no other block is connected
-            } else {
-                // convert the json value to a execution type value
-                let value = self
-                    .logic
-                    .type_from_json(n.connection_type.clone(), n.value.clone())?;
-
-                results.push(Register {
-                    block_id: block.block_id,
-                    node_id: n.id,
-                    value,
-                });
+    /// Returns the compiled model, which can be shared to execute it
+    /// from many threads at once
+    pub fn get_model(&mut self) -> Result<Arc<Model>> {
+        match &self.model {
+            Some(model) => Ok(model.clone()),
+            None => {
+                let model = Arc::new(Model::compile(&self.raw_code, &self.logic)?);
+                self.model = Some(model.clone());
+                Ok(model)
             }
         }
+    }
 
-        let exec_block = self
-            .logic
-            .get_block(block.block_type_id)
-            .ok_or("The given Block Type is not avilable")?;
-
-        // Start blocks don't get executed, to not override the values
-        // pushed inside the register by the start of the execution
-        if exec_block.get_type() == ExecutionBlockType::Start {
-            return Ok(vec![]);
-        }
-
-        let ret = exec_block.execute(results, block_id)?;
-
-        Ok(ret)
+    /// Executes the model with the given inputs for the start block.
+    ///
+    /// Returns the inputs of the end block which was reached, or none when
+    /// the execution ended without reaching an end block.
+    pub fn execute(
+        &mut self,
+        inputs: Vec<Box<dyn types::ExecutionType>>,
+    ) -> Result<Option<Vec<Box<dyn types::ExecutionType>>>> {
+        let model = self.get_model()?;
+        model.execute_with(&mut self.context, inputs)
     }
 }
 
@@ -450,3 +679,262 @@ pub fn log(out: String) {
         println!("#> {}", out);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::integer::Integer;
+    use crate::test_util::{logic, Code, Start};
+    use crate::types::{Execution, Next};
+    use crate::validation::ViolationKind;
+    use serde_json::json;
+
+    ExecutionBlockHelper!(
+        id: 20,
+        name: DivideWithRest,
+        typ: Static,
+        path: crate,
+
+        fn execute(
+            #[pin(label = "Dividend", description = "The number to divide")] value: Integer,
+            divisor: Integer,
+        ) -> Result<(quotient: Integer, #[pin(label = "Rest")] rest: Integer)> {
+            Ok((value.checked_div(divisor).ok_or(Error::DivisionByZero)?, value % divisor))
+        }
+    );
+
+    ExecutionBlockHelper!(
+        id: 21,
+        name: Choose,
+        typ: Normal,
+        next: [First, Second, Third],
+        path: crate,
+
+        fn execute(index: Integer, _a: Integer, _b: Integer) -> (Next) {
+            Next(index as usize)
+        }
+    );
+
+    ExecutionBlockHelper!(
+        id: 22,
+        name: Stop,
+        typ: End,
+        path: crate,
+
+        fn execute() -> () {}
+    );
+
+    fn results(values: Option<Vec<Box<dyn types::ExecutionType>>>) -> Vec<serde_json::Value> {
+        values.unwrap().iter().map(|v| v.to_json()).collect()
+    }
+
+    #[test]
+    fn block_ids_are_unique() {
+        let mut logic = Logic::default();
+        assert!(logic.add_block(Box::new(Start {})).is_ok());
+        assert!(logic.add_block(Box::new(Start {})).is_err());
+
+        assert!(logic.remove_block(1));
+        assert!(logic.get_block(1).is_none());
+        assert!(logic.add_block(Box::new(Start {})).is_ok());
+    }
+
+    #[test]
+    fn execute_returns_the_inputs_of_the_end_block() {
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .connect((1, 0), (2, 0))
+            .connect((1, 3), (2, 2));
+
+        let mut executer = Executer::with_logic(code.to_json(), logic());
+        let values = executer.execute(vec![Box::new(5i64)]).unwrap();
+        assert_eq!(results(values), vec![json!(5)]);
+
+        // without a connection to the end block nothing is returned
+        let code = Code::new().start(1).end(2).value(2, 2, json!(1));
+        let mut executer = Executer::with_logic(code.to_json(), logic());
+        assert!(executer.execute(vec![Box::new(5i64)]).unwrap().is_none());
+    }
+
+    #[test]
+    fn end_blocks_without_inputs_are_told_apart_from_no_end_block() {
+        let mut logic = logic();
+        logic.add_block(Box::new(Stop {})).unwrap();
+
+        let code = Code::new()
+            .start(1)
+            .block(2, 22, &[(0, "input", "Execution")])
+            .connect((1, 0), (2, 0));
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+        assert!(model.execute(vec![]).unwrap().unwrap().is_empty());
+
+        let code = Code::new()
+            .start(1)
+            .block(2, 22, &[(0, "input", "Execution")]);
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+        assert!(model.execute(vec![]).unwrap().is_none());
+    }
+
+    #[test]
+    fn executer_with_a_custom_logic() {
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .connect((1, 0), (2, 0))
+            .connect((1, 3), (2, 2));
+
+        let logic = Arc::new(logic());
+        let mut executer = Executer::with_logic(code.to_json(), logic.clone());
+        assert!(Arc::ptr_eq(executer.get_logic(), &logic));
+        assert_eq!(
+            results(executer.execute(vec![Box::new(3i64)]).unwrap()),
+            vec![json!(3)]
+        );
+
+        // the default logic has no start and end blocks of the tests
+        executer.set_logic(Logic::default());
+        match executer.execute(vec![Box::new(3i64)]) {
+            Err(Error::ValidationError(v)) => assert_eq!(
+                v.into_iter().map(|v| v.kind).collect::<Vec<_>>(),
+                vec![
+                    ViolationKind::UnknownBlockType { block_type_id: 1 },
+                    ViolationKind::UnknownBlockType { block_type_id: 2 },
+                    ViolationKind::NoStartBlock,
+                ]
+            ),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn model_is_shared_between_threads() {
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .connect((1, 0), (2, 0))
+            .connect((1, 3), (2, 2));
+
+        let mut executer = Executer::with_logic(code.to_json(), logic());
+        let model = executer.get_model().unwrap();
+        assert!(Arc::ptr_eq(&model, &executer.get_model().unwrap()));
+
+        let threads = (0..4i64)
+            .map(|i| {
+                let model = model.clone();
+                std::thread::spawn(move || {
+                    let mut context = Context::new();
+                    (0..100)
+                        .map(|j| model.execute_with(&mut context, vec![Box::new(i * 100 + j)]))
+                        .map(|r| r.unwrap().unwrap()[0].to_json())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (i, t) in threads.into_iter().enumerate() {
+            let expected = (0..100).map(|j| json!(i * 100 + j)).collect::<Vec<_>>();
+            assert_eq!(t.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn literals_are_parsed_strictly() {
+        let logic = Logic::default();
+        let value = |typ: &str, json| {
+            logic
+                .type_from_json(typ.to_string(), json)
+                .map(|v| v.to_json())
+        };
+
+        assert_eq!(value("Integer", json!(" 42 ")).unwrap(), json!(42));
+        assert_eq!(value("Float", json!("1e3")).unwrap(), json!(1000.0));
+        assert_eq!(value("String", json!("")).unwrap(), json!(""));
+
+        for (typ, json) in [
+            ("Integer", json!("12a")),
+            ("Integer", json!(1.5)),
+            ("Integer", json!("")),
+            ("Float", json!("one")),
+            ("Boolean", json!("1")),
+            ("Decimal", json!("1.2.3")),
+            ("Date", json!("2021-02-29")),
+            ("List", json!("")),
+        ]
+        .iter()
+        {
+            assert!(value(typ, json.clone()).is_err(), "{} {}", typ, json);
+        }
+
+        // no type falls back to a default for a missing value
+        for typ in logic.get_connection_json() {
+            let typ = typ["type"].as_str().unwrap();
+            assert!(value(typ, serde_json::Value::Null).is_err(), "{}", typ);
+        }
+        assert!(value("Unknown", json!(1)).is_err());
+    }
+
+    #[test]
+    fn nodes_have_the_names_of_their_pins() {
+        let block = DivideWithRest {};
+        let nodes = block.get_json()["nodes"].clone();
+
+        assert_eq!(
+            nodes,
+            json!([
+                {
+                    "id": 2,
+                    "io": "input",
+                    "type": "Integer",
+                    "name": "value",
+                    "label": "Dividend",
+                    "description": "The number to divide",
+                },
+                { "id": 3, "io": "output", "type": "Integer", "name": "quotient" },
+                { "id": 4, "io": "input", "type": "Integer", "name": "divisor" },
+                { "id": 5, "io": "output", "type": "Integer", "name": "rest", "label": "Rest" },
+            ])
+        );
+
+        // blocks without pins keep their nodes unnamed
+        let node = NodeDefinition::new(2, "input", "Integer", None).with_pin(&Pin::new(""));
+        assert_eq!(
+            node.get_json(),
+            json!({ "id": 2, "io": "input", "type": "Integer" })
+        );
+    }
+
+    #[test]
+    fn selected_execution_outputs_have_the_nodes_of_the_block() {
+        let block = Choose {};
+        let nodes = block
+            .get_nodes()
+            .into_iter()
+            .filter(|n| n.io == "output" && n.typ == "Execution")
+            .map(|n| n.id)
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, vec![1, 9, 11]);
+
+        let run = |index: Integer| {
+            let inputs = [index, 0, 0]
+                .iter()
+                .enumerate()
+                .map(|(i, v)| Register {
+                    block_id: 1,
+                    node_id: i as u32 * 2 + 2,
+                    value: Box::new(*v),
+                })
+                .collect();
+            block.execute(inputs, 1)
+        };
+
+        for (index, node) in nodes.iter().enumerate() {
+            let out = run(index as Integer).unwrap();
+            assert_eq!(out.len(), 1);
+            assert_eq!(out[0].node_id, *node);
+            assert!(out[0].value.is::<Execution>());
+        }
+
+        assert!(run(3).is_err());
+    }
+}
@@ -0,0 +1,1134 @@
+use crate::error::{Error, Result};
+use crate::types::{Execution, ExecutionType};
+use crate::validation::{Literals, Violation, ViolationKind};
+use crate::{Block, ExecutionBlock, ExecutionBlockType, Logic, LoopState, LoopStep, Register};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Number of runs of a loop body after which a loop fails, unless
+/// another limit is set for the context
+pub const DEFAULT_MAX_ITERATIONS: usize = 1_000_000;
+
+/// Number of normal, loop and end blocks a run executes after which it
+/// fails, unless another limit is set for the context
+pub const DEFAULT_MAX_STEPS: usize = 100_000_000;
+
+/// State of a single execution of a model.
+///
+/// A context is cheap to create and can be reused for many executions, but
+/// only used by one execution at a time. The compiled model itself is never
+/// changed while it is executed.
+#[derive(Debug)]
+pub struct Context {
+    /// Register with one slot per node of the plan
+    pub(crate) register: Vec<Option<Box<dyn ExecutionType>>>,
+    /// Number of times every block was executed within this run
+    runs: Vec<u64>,
+    /// For cached static blocks, the runs of the blocks they depend on
+    /// at the time their outputs were stored
+    cached: Vec<Option<u64>>,
+    /// Number of runs of a loop body after which the loop fails
+    max_iterations: usize,
+    /// Number of executed blocks after which the run fails
+    max_steps: usize,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            register: vec![],
+            runs: vec![],
+            cached: vec![],
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+}
+
+impl Context {
+    pub fn new() -> Context {
+        Context::default()
+    }
+
+    /// Sets the number of runs of a loop body after which a loop fails
+    /// with `Error::IterationLimit`, so a loop which never ends can't
+    /// block the execution forever
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    pub fn get_max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Sets the number of normal, loop and end blocks a run may execute
+    /// before it fails with `Error::IterationLimit`. Unlike the limit of a
+    /// loop, it also stops execution outputs which are connected in a
+    /// cycle, like a loop body which runs its own loop block again.
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn get_max_steps(&self) -> usize {
+        self.max_steps
+    }
+
+    /// Returns the values of the register as json, with null for every
+    /// value which is not set. Helps to debug a failed execution.
+    pub fn get_register_json(&self) -> serde_json::Value {
+        self.register
+            .iter()
+            .map(|v| v.as_ref().map(|v| v.to_json()).unwrap_or_default())
+            .collect()
+    }
+}
+
+/// Code of a model compiled into an execution plan.
+///
+/// All blocks are indexed, the sources of all inputs are resolved, the literal
+/// values are parsed and the static blocks every block depends on are sorted
+/// in the order they need to be executed. Executing the plan doesn't need any
+/// lookups by id or name anymore.
+pub(crate) struct Plan {
+    blocks: Vec<PlanBlock>,
+    start: usize,
+    slots: usize,
+}
+
+/// Block of the code with all references resolved
+struct PlanBlock {
+    block_id: u32,
+    block_type_id: u32,
+    typ: ExecutionBlockType,
+    exec: Arc<dyn ExecutionBlock>,
+    /// First slot of the block in the register, followed by one slot per node id
+    slot: usize,
+    slot_count: usize,
+    /// Node ids of the data outputs
+    outputs: Vec<u32>,
+    /// Non execution inputs, ordered by their node id
+    inputs: Vec<Input>,
+    /// Static blocks to execute before the inputs can be read, static
+    /// blocks themselves are run by the blocks reading them and have none
+    statics: Vec<usize>,
+    /// Number of inputs of a loop block read before the first run of the
+    /// loop body and the static blocks they depend on
+    first_inputs: usize,
+    first_statics: Vec<usize>,
+    /// Normal and loop blocks whose outputs a static block depends on
+    sources: Vec<usize>,
+    /// Whether the outputs of a static block can be reused within a run
+    cacheable: bool,
+    /// Connected block of every execution output, by the index of the output
+    next: Vec<Option<usize>>,
+    /// Index of the execution output of every node id, none for other nodes
+    next_index: Vec<Option<usize>>,
+}
+
+/// Resolved source of an input
+enum Input {
+    /// Value defined within the code
+    Literal {
+        node_id: u32,
+        value: Box<dyn ExecutionType>,
+    },
+    /// Value of an output of another block
+    Output {
+        slot: usize,
+        block_id: u32,
+        node_id: u32,
+        block_type_id: u32,
+    },
+}
+
+impl Plan {
+    /// Compiles the validated code into an execution plan, taking the
+    /// values of its inputs without a connection from the validation
+    pub(crate) fn compile(code: &[Block], logic: &Logic, mut literals: Literals) -> Result<Plan> {
+        let index = code
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.block_id, i))
+            .collect::<HashMap<u32, usize>>();
+
+        // bind the execution blocks and reserve the slots in the register
+        let mut blocks = vec![];
+        let mut slots = 0;
+
+        for block in code {
+            let exec = logic
+                .get_shared_block(block.block_type_id)
+                .ok_or(Error::UnknownBlockType {
+                    block_id: block.block_id,
+                    block_type_id: block.block_type_id,
+                })?
+                .clone();
+
+            let slot_count = block
+                .nodes
+                .iter()
+                .map(|n| n.id)
+                .chain(exec.get_nodes().iter().map(|n| n.id))
+                .max()
+                .map(|id| id as usize + 1)
+                .unwrap_or_default();
+
+            let outputs = exec
+                .get_nodes()
+                .iter()
+                .filter(|n| n.io == "output" && n.typ != "Execution")
+                .map(|n| n.id)
+                .collect();
+
+            blocks.push(PlanBlock {
+                block_id: block.block_id,
+                block_type_id: block.block_type_id,
+                typ: exec.get_type(),
+                exec,
+                slot: slots,
+                slot_count,
+                outputs,
+                inputs: vec![],
+                statics: vec![],
+                first_inputs: 0,
+                first_statics: vec![],
+                sources: vec![],
+                cacheable: false,
+                next: vec![],
+                next_index: vec![],
+            });
+
+            slots += slot_count;
+        }
+
+        let get_index = |block_id: u32| {
+            index
+                .get(&block_id)
+                .copied()
+                .ok_or(Error::UnknownBlock { block_id })
+        };
+
+        // resolve the inputs and the execution outputs
+        let mut depends = vec![vec![]; code.len()];
+        let mut first_depends = vec![vec![]; code.len()];
+        let mut sources = vec![vec![]; code.len()];
+
+        for (i, block) in code.iter().enumerate() {
+            // inputs fed back from a loop body are read after all others
+            let exec = &blocks[i].exec;
+            let first_inputs = exec
+                .get_nodes()
+                .iter()
+                .filter(|n| n.io == "input" && n.typ != "Execution")
+                .count()
+                .saturating_sub(exec.get_feedback_inputs());
+
+            let mut nodes = block.nodes.iter().collect::<Vec<_>>();
+            nodes.sort_by_key(|n| n.id);
+
+            // the execution outputs in the order the block selects them by
+            let mut next_index = vec![None; blocks[i].slot_count];
+            let next_nodes = exec
+                .get_nodes()
+                .iter()
+                .filter(|n| n.io == "output" && n.typ == "Execution")
+                .map(|n| n.id)
+                .collect::<Vec<_>>();
+
+            for (k, &id) in next_nodes.iter().enumerate() {
+                let target = match nodes
+                    .iter()
+                    .find(|n| n.id == id)
+                    .and_then(|n| n.connections.first())
+                {
+                    Some(c) => Some(get_index(c.end_block)?),
+                    None => None,
+                };
+
+                next_index[id as usize] = Some(k);
+                blocks[i].next.push(target);
+            }
+            blocks[i].next_index = next_index;
+
+            for n in nodes {
+                if n.connection_type == "Execution" {
+                    continue;
+                }
+
+                if n.node_type != "input" {
+                    continue;
+                }
+
+                let input = match n.connections.first() {
+                    // when another block is connected
+                    Some(con) => {
+                        let source = &blocks[get_index(con.start_block)?];
+
+                        match source.typ {
+                            ExecutionBlockType::Static => {
+                                if blocks[i].inputs.len() < first_inputs {
+                                    first_depends[i].push(get_index(con.start_block)?);
+                                }
+                                depends[i].push(get_index(con.start_block)?)
+                            }
+                            ExecutionBlockType::Normal | ExecutionBlockType::Loop => {
+                                sources[i].push(get_index(con.start_block)?)
+                            }
+                            _ => {}
+                        }
+
+                        Input::Output {
+                            slot: source.slot + con.start_node as usize,
+                            block_id: con.start_block,
+                            node_id: con.start_node,
+                            block_type_id: source.block_type_id,
+                        }
+                    }
+                    // the value parsed while validating the code
+                    None => Input::Literal {
+                        node_id: n.id,
+                        value: literals.remove(&(block.block_id, n.id)).ok_or_else(|| {
+                            Error::UnknownConnectionType {
+                                block_id: block.block_id,
+                                node_id: n.id,
+                                block_type_id: block.block_type_id,
+                                connection_type: n.connection_type.clone(),
+                            }
+                        })?,
+                    },
+                };
+
+                blocks[i].inputs.push(input);
+            }
+
+            blocks[i].first_inputs = blocks[i].inputs.len().min(first_inputs);
+        }
+
+        // sort the static blocks every block depends on by one order of all blocks
+        let rank = rank_statics(&depends, &blocks)?;
+        let mut seen = vec![usize::MAX; blocks.len()];
+
+        // static blocks are run by the blocks which read them, so only
+        // those need the static blocks they depend on
+        for i in 0..blocks.len() {
+            if blocks[i].typ == ExecutionBlockType::Static {
+                continue;
+            }
+
+            blocks[i].statics = collect_statics(i * 2, &depends[i], &depends, &rank, &mut seen);
+            blocks[i].first_statics =
+                collect_statics(i * 2 + 1, &first_depends[i], &depends, &rank, &mut seen);
+        }
+
+        // the outputs of pure static blocks are valid until one of the
+        // normal or loop blocks they depend on gets executed again. The
+        // blocks are visited in their rank, so the static blocks a block
+        // depends on already carry everything they depend on themselves.
+        let mut order = (0..blocks.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&i| rank[i]);
+
+        for i in order {
+            let mut s = sources[i].clone();
+            s.extend(depends[i].iter().flat_map(|&d| blocks[d].sources.iter()));
+            s.sort_unstable();
+            s.dedup();
+
+            blocks[i].cacheable =
+                blocks[i].exec.is_pure() && depends[i].iter().all(|&d| blocks[d].cacheable);
+            blocks[i].sources = s;
+        }
+
+        let start = blocks
+            .iter()
+            .position(|b| b.typ == ExecutionBlockType::Start)
+            .ok_or(Error::NoStartBlock)?;
+
+        Ok(Plan {
+            blocks,
+            start,
+            slots,
+        })
+    }
+
+    /// Executes the plan with the given inputs for the start block, returns
+    /// the inputs of the end block or none when no end block was reached
+    pub(crate) fn execute(
+        &self,
+        ctx: &mut Context,
+        inputs: Vec<Box<dyn ExecutionType>>,
+    ) -> Result<Option<Vec<Box<dyn ExecutionType>>>> {
+        ctx.register.clear();
+        ctx.register.resize_with(self.slots, || None);
+        ctx.runs.clear();
+        ctx.runs.resize(self.blocks.len(), 0);
+        ctx.cached.clear();
+        ctx.cached.resize(self.blocks.len(), None);
+
+        let start = &self.blocks[self.start];
+
+        for (i, v) in inputs.into_iter().enumerate() {
+            let node_id = (i * 2) + 3;
+
+            if node_id >= start.slot_count {
+                return Err("More inputs given than the start block accepts".into());
+            }
+
+            ctx.register[start.slot + node_id] = Some(v);
+        }
+
+        let mut next = start.next.first().copied().flatten();
+        let mut loops: Vec<(usize, LoopState)> = vec![];
+        let mut steps = 0;
+
+        loop {
+            // when an execution chain ends, the innermost running loop continues
+            let (index, resume) = match next {
+                Some(i) => (i, false),
+                None => match loops.last() {
+                    Some((i, _)) => (*i, true),
+                    // When this point is reached, the programm has ended sucessfully
+                    None => return Ok(None),
+                },
+            };
+
+            let block = &self.blocks[index];
+
+            // execution outputs connected in a cycle would run forever
+            steps += 1;
+            if steps > ctx.max_steps {
+                return Err(Error::IterationLimit {
+                    block_id: block.block_id,
+                    block_type_id: block.block_type_id,
+                    limit: ctx.max_steps,
+                });
+            }
+
+            let next_node = match block.typ {
+                // end blocks return their inputs as the result of the execution
+                ExecutionBlockType::End => {
+                    let inputs = self.read_inputs(block, ctx, false)?;
+                    return Ok(Some(inputs.into_iter().map(|r| r.value).collect()));
+                }
+                ExecutionBlockType::Loop => {
+                    if !resume {
+                        loops.push((index, LoopState::default()));
+                    }
+
+                    let inputs = self.read_inputs(block, ctx, !resume)?;
+                    let state = match loops.last_mut() {
+                        Some((_, state)) => state,
+                        None => {
+                            return Err(Error::UnknownBlock {
+                                block_id: block.block_id,
+                            })
+                        }
+                    };
+
+                    let step = guard(block, || block.exec.iterate(inputs, block.block_id, state))?;
+
+                    match step {
+                        LoopStep::Body(_) if state.iteration >= ctx.max_iterations => {
+                            return Err(Error::IterationLimit {
+                                block_id: block.block_id,
+                                block_type_id: block.block_type_id,
+                                limit: ctx.max_iterations,
+                            });
+                        }
+                        LoopStep::Body(values) => {
+                            state.iteration += 1;
+                            ctx.runs[index] += 1;
+                            self.store(block, ctx, values)?;
+                            0
+                        }
+                        LoopStep::Completed(values) => {
+                            loops.pop();
+                            ctx.runs[index] += 1;
+                            self.store(block, ctx, values)?;
+                            1
+                        }
+                    }
+                }
+                // Start blocks don't get executed, to not override the values
+                // pushed inside the register by the start of the execution
+                ExecutionBlockType::Start => 0,
+                _ => {
+                    // handle the input of the node and execute the node
+                    let values = self.exec_block(block, ctx)?;
+                    ctx.runs[index] += 1;
+
+                    self.store(block, ctx, values)?.unwrap_or(0)
+                }
+            };
+
+            next = block.next.get(next_node).copied().flatten();
+        }
+    }
+
+    fn exec_block(&self, block: &PlanBlock, ctx: &mut Context) -> Result<Vec<Register>> {
+        let inputs = self.read_inputs(block, ctx, false)?;
+
+        guard(block, || block.exec.execute(inputs, block.block_id))
+    }
+
+    /// Executes the static blocks the block depends on and reads its inputs.
+    /// Before the first run of a loop body, the inputs fed back from the
+    /// loop body are left out. After it, loop blocks which keep their inputs
+    /// only get the inputs fed back from the loop body.
+    fn read_inputs(
+        &self,
+        block: &PlanBlock,
+        ctx: &mut Context,
+        first: bool,
+    ) -> Result<Vec<Register>> {
+        let (statics, inputs) = if first {
+            (&block.first_statics, &block.inputs[..block.first_inputs])
+        } else if block.typ == ExecutionBlockType::Loop && block.exec.keeps_inputs() {
+            (&block.statics, &block.inputs[block.first_inputs..])
+        } else {
+            (&block.statics, &block.inputs[..])
+        };
+
+        for &i in statics {
+            let s = &self.blocks[i];
+
+            // reuse the outputs when nothing they depend on has changed
+            let runs = s.sources.iter().map(|&d| ctx.runs[d]).sum::<u64>();
+            if s.cacheable && ctx.cached[i] == Some(runs) {
+                continue;
+            }
+
+            let inputs = self.collect_inputs(s, &s.inputs, ctx)?;
+
+            let values = guard(s, || s.exec.execute(inputs, s.block_id))?;
+
+            self.store(s, ctx, values)?;
+
+            if s.cacheable {
+                ctx.cached[i] = Some(runs);
+            }
+        }
+
+        self.collect_inputs(block, inputs, ctx)
+    }
+
+    fn collect_inputs(
+        &self,
+        block: &PlanBlock,
+        inputs: &[Input],
+        ctx: &Context,
+    ) -> Result<Vec<Register>> {
+        let mut results = vec![];
+
+        for input in inputs {
+            results.push(match input {
+                Input::Literal { node_id, value } => Register {
+                    block_id: block.block_id,
+                    node_id: *node_id,
+                    value: value.duplicate(),
+                },
+                Input::Output {
+                    slot,
+                    block_id,
+                    node_id,
+                    block_type_id,
+                } => {
+                    let value =
+                        ctx.register[*slot]
+                            .as_ref()
+                            .ok_or(Error::MissingRegisterValue {
+                                block_id: *block_id,
+                                node_id: *node_id,
+                                block_type_id: *block_type_id,
+                            })?;
+
+                    Register {
+                        block_id: *block_id,
+                        node_id: *node_id,
+                        value: value.duplicate(),
+                    }
+                }
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Stores the outputs of a block in the register and returns the index
+    /// of the execution output selected by the block.
+    ///
+    /// The outputs of the previous run are removed first, so an output the
+    /// block didn't set, like after taking its error output, can't be read.
+    /// Loop blocks keep their outputs when completed.
+    fn store(
+        &self,
+        block: &PlanBlock,
+        ctx: &mut Context,
+        values: Vec<Register>,
+    ) -> Result<Option<usize>> {
+        let mut next = None;
+
+        if block.typ != ExecutionBlockType::Loop {
+            for &id in &block.outputs {
+                ctx.register[block.slot + id as usize] = None;
+            }
+        }
+
+        for r in values {
+            let invalid = || Error::InvalidNextNode {
+                block_id: block.block_id,
+                node_id: r.node_id,
+                block_type_id: block.block_type_id,
+            };
+
+            // an execution value selects the execution output to run next
+            if r.value.is::<Execution>() {
+                let index = block.next_index.get(r.node_id as usize).copied();
+                next = Some(index.flatten().ok_or_else(invalid)?);
+                continue;
+            }
+
+            if r.node_id as usize >= block.slot_count {
+                return Err(invalid());
+            }
+
+            ctx.register[block.slot + r.node_id as usize] = Some(r.value);
+        }
+
+        Ok(next)
+    }
+}
+
+/// Orders all blocks, so every block comes after the static blocks it
+/// depends on, and returns the position of every block within this order
+fn rank_statics(depends: &[Vec<usize>], blocks: &[PlanBlock]) -> Result<Vec<usize>> {
+    let mut rank = vec![usize::MAX; depends.len()];
+    let mut visiting = vec![false; depends.len()];
+    let mut next = 0;
+
+    for root in 0..depends.len() {
+        let mut stack = vec![(root, false)];
+
+        while let Some((i, expanded)) = stack.pop() {
+            if expanded {
+                visiting[i] = false;
+                rank[i] = next;
+                next += 1;
+                continue;
+            }
+
+            if rank[i] != usize::MAX {
+                continue;
+            }
+
+            if visiting[i] {
+                return Err(Error::ValidationError(vec![Violation {
+                    block_id: Some(blocks[i].block_id),
+                    node_id: None,
+                    kind: ViolationKind::StaticCycle,
+                }]));
+            }
+
+            visiting[i] = true;
+            stack.push((i, true));
+            stack.extend(
+                depends[i]
+                    .iter()
+                    .filter(|&&d| rank[d] == usize::MAX)
+                    .map(|&d| (d, false)),
+            );
+        }
+    }
+
+    Ok(rank)
+}
+
+/// Collects the static blocks reachable through the given roots, sorted by
+/// their rank. The blocks marked with the given mark in `seen` are skipped,
+/// so the marks can be reused for every call without clearing them.
+fn collect_statics(
+    mark: usize,
+    roots: &[usize],
+    depends: &[Vec<usize>],
+    rank: &[usize],
+    seen: &mut [usize],
+) -> Vec<usize> {
+    let mut statics = vec![];
+    let mut stack = roots.to_vec();
+
+    while let Some(i) = stack.pop() {
+        if seen[i] == mark {
+            continue;
+        }
+
+        seen[i] = mark;
+        statics.push(i);
+        stack.extend(depends[i].iter().filter(|&&d| seen[d] != mark));
+    }
+
+    statics.sort_unstable_by_key(|&i| rank[i]);
+    statics
+}
+
+/// Runs a block and tags its errors with the block.
+///
+/// On native targets a panic inside the block is caught and returned as
+/// `BlockPanicked`, so a faulty block can't take down the host thread.
+/// WebAssembly aborts on a panic, there it can't be caught.
+fn guard<T>(block: &PlanBlock, run: impl FnOnce() -> Result<T>) -> Result<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)).unwrap_or_else(|e| {
+        let message = if let Some(s) = e.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = e.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Unknown panic".to_string()
+        };
+
+        Err(Error::BlockPanicked {
+            block_id: block.block_id,
+            block_type_id: block.block_type_id,
+            message,
+        })
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    let res = run();
+
+    res.map_err(|e| e.in_block(block.block_id, block.block_type_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::integer::Integer;
+    use crate::error::{Error, Result};
+    use crate::test_util::{execute, execute_block, logic, Code};
+    use crate::types::{Execution, ExecutionType};
+    use crate::validation::ViolationKind;
+    use crate::{Context, ExecutionBlock, ExecutionBlockType, Model, Register};
+    use bme_macro::ExecutionBlockHelper;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTED: AtomicUsize = AtomicUsize::new(0);
+
+    ExecutionBlockHelper!(
+        id: 10,
+        name: Count,
+        typ: Static,
+        path: crate,
+
+        fn execute(inp: Integer) -> (Integer) {
+            COUNTED.fetch_add(1, Ordering::SeqCst);
+            (inp)
+        }
+    );
+
+    ExecutionBlockHelper!(
+        id: 11,
+        name: Panic,
+        typ: Normal,
+        path: crate,
+
+        fn execute(inp: Integer) -> () {
+            if inp > 0 {
+                panic!("no {}", inp);
+            }
+        }
+    );
+
+    ExecutionBlockHelper!(
+        id: 12,
+        name: StaticPanic,
+        typ: Static,
+        path: crate,
+
+        fn execute(inp: Integer) -> (Integer) {
+            if inp > 0 {
+                std::panic::panic_any(inp);
+            }
+            (inp)
+        }
+    );
+
+    ExecutionBlockHelper!(
+        id: 13,
+        name: Halve,
+        typ: Normal,
+        error_output: true,
+        path: crate,
+
+        fn execute(inp: Integer) -> Result<(Integer), String> {
+            if inp % 2 != 0 {
+                return Err(format!("{} is odd", inp));
+            }
+            Ok(inp / 2)
+        }
+    );
+
+    /// Normal block which returns a value of the given type for the node
+    #[derive(Debug)]
+    struct Output {
+        id: u32,
+        node_id: u32,
+        execution: bool,
+    }
+
+    impl ExecutionBlock for Output {
+        fn get_id(&self) -> u32 {
+            self.id
+        }
+
+        fn get_name(&self) -> &str {
+            "Output"
+        }
+
+        fn get_type(&self) -> ExecutionBlockType {
+            ExecutionBlockType::Normal
+        }
+
+        fn execute(&self, _input: Vec<Register>, block_id: u32) -> Result<Vec<Register>> {
+            let value: Box<dyn ExecutionType> = if self.execution {
+                Box::new(Execution::new())
+            } else {
+                Box::new(1 as Integer)
+            };
+
+            Ok(vec![Register {
+                block_id,
+                node_id: self.node_id,
+                value,
+            }])
+        }
+
+        fn get_outputs(&self) -> &'static [&'static str] {
+            &["Integer"]
+        }
+    }
+
+    /// Adds a static block which adds the value to its first input
+    fn add(code: Code, block_id: u32, value: i64) -> Code {
+        code.block(
+            block_id,
+            64_001,
+            &[
+                (2, "input", "Integer"),
+                (3, "output", "Integer"),
+                (4, "input", "Integer"),
+            ],
+        )
+        .value(block_id, 4, json!(value))
+    }
+
+    #[test]
+    fn long_chains_of_static_blocks() {
+        let mut code = Code::new().start(1).end(2).connect((1, 0), (2, 0));
+
+        // every block adds one to the output of the block before
+        let mut last = (1, 3);
+        for block_id in 3..10_003 {
+            code = add(code, block_id, 1).connect(last, (block_id, 2));
+            last = (block_id, 3);
+        }
+        code = code.connect(last, (2, 2));
+
+        assert_eq!(execute(&code, 7).unwrap(), vec![json!(10_007)]);
+    }
+
+    #[test]
+    fn static_blocks_run_after_their_inputs() {
+        // 3 = start + 1, 4 = 3 + 2, 5 = 3 + 3, 6 = 4 + 5
+        let code = add(Code::new().start(1).end(2), 3, 1)
+            .connect((1, 0), (2, 0))
+            .connect((1, 3), (3, 2));
+        let code = add(add(code, 4, 2), 5, 3)
+            .connect((3, 3), (4, 2))
+            .connect((3, 3), (5, 2));
+        let code = add(code, 6, 0)
+            .connect((4, 3), (6, 2))
+            .connect((5, 3), (6, 4))
+            .connect((6, 3), (2, 2));
+
+        assert_eq!(execute(&code, 1).unwrap(), vec![json!(9)]);
+    }
+
+    #[test]
+    fn static_cycles_are_rejected() {
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .value(2, 2, json!(0))
+            .connect((1, 0), (2, 0));
+        let code = add(add(code, 3, 1), 4, 1)
+            .connect((3, 3), (4, 2))
+            .connect((4, 3), (3, 2));
+
+        match execute(&code, 1) {
+            Err(Error::ValidationError(v)) => {
+                assert_eq!(v.len(), 1);
+                assert_eq!(v[0].kind, ViolationKind::StaticCycle);
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn static_blocks_run_once_per_run() {
+        // the counted block is read by every call of the loop and by the end block
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .block(3, 10, &[(2, "input", "Integer"), (3, "output", "Integer")])
+            .block(
+                4,
+                60_003,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "Integer"),
+                    (3, "output", "Integer"),
+                    (5, "output", "Execution"),
+                ],
+            )
+            .connect((1, 0), (4, 0))
+            .connect((4, 5), (2, 0))
+            .connect((1, 3), (3, 2))
+            .connect((3, 3), (4, 2))
+            .connect((3, 3), (2, 2));
+
+        let mut logic = logic();
+        logic.add_block(Box::new(Count {})).unwrap();
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+
+        for run in 1..4 {
+            let values = model
+                .execute(vec![Box::new(run as Integer)])
+                .unwrap()
+                .unwrap();
+            assert_eq!(values[0].to_json(), json!(run));
+            assert_eq!(COUNTED.load(Ordering::SeqCst), run);
+        }
+    }
+
+    #[test]
+    fn panics_become_errors() {
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .value(2, 2, json!(0))
+            .block(
+                3,
+                11,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "Integer"),
+                ],
+            )
+            .connect((1, 0), (3, 0))
+            .connect((3, 1), (2, 0))
+            .connect((1, 3), (3, 2));
+
+        let mut logic = logic();
+        logic.add_block(Box::new(Panic {})).unwrap();
+        logic.add_block(Box::new(StaticPanic {})).unwrap();
+
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+        match model.execute(vec![Box::new(4 as Integer)]) {
+            Err(Error::BlockPanicked {
+                block_id: 3,
+                block_type_id: 11,
+                message,
+            }) => assert_eq!(message, "no 4"),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // static blocks are guarded as well, also without a message
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .block(4, 12, &[(2, "input", "Integer"), (3, "output", "Integer")])
+            .connect((1, 0), (2, 0))
+            .connect((1, 3), (4, 2))
+            .connect((4, 3), (2, 2));
+
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+        match model.execute(vec![Box::new(4 as Integer)]) {
+            Err(Error::BlockPanicked {
+                block_id: 4,
+                block_type_id: 12,
+                message,
+            }) => assert_eq!(message, "Unknown panic"),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn failed_blocks_continue_with_their_error_output() {
+        let block = Halve {};
+        assert_eq!(block.get_next_outputs(), &["Next", "Error"]);
+        let error = block
+            .get_nodes()
+            .into_iter()
+            .find(|n| n.name == Some("Error"));
+        assert_eq!(error.map(|n| n.id), Some(7));
+
+        let outputs = execute_block(&block, vec![Box::new(3 as Integer)]).unwrap();
+        assert_eq!(outputs, vec![json!(null), json!("3 is odd")]);
+
+        // both execution outputs lead to the end block, which reads the half
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .block(
+                3,
+                13,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "Integer"),
+                    (3, "output", "Integer"),
+                    (5, "output", "String"),
+                    (7, "output", "Execution"),
+                ],
+            )
+            .connect((1, 0), (3, 0))
+            .connect((3, 1), (2, 0))
+            .connect((3, 7), (2, 0))
+            .connect((1, 3), (3, 2))
+            .connect((3, 3), (2, 2));
+
+        let mut logic = logic();
+        logic.add_block(Box::new(Halve {})).unwrap();
+        let model = Model::compile(&code.to_json(), &logic).unwrap();
+
+        let values = model
+            .execute(vec![Box::new(4 as Integer)])
+            .unwrap()
+            .unwrap();
+        assert_eq!(values[0].to_json(), json!(2));
+
+        // the half of the run before isn't kept after taking the error output
+        match model.execute(vec![Box::new(3 as Integer)]) {
+            Err(Error::MissingRegisterValue {
+                block_id: 3,
+                node_id: 3,
+                block_type_id: 13,
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn execution_cycles_are_stopped() {
+        let print = |code: Code, block_id| {
+            code.block(
+                block_id,
+                60_001,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "String"),
+                ],
+            )
+            .value(block_id, 2, json!("cycle"))
+        };
+
+        // two blocks which run each other
+        let code = print(print(Code::new().start(1), 3), 4)
+            .connect((1, 0), (3, 0))
+            .connect((3, 1), (4, 0))
+            .connect((4, 1), (3, 0));
+
+        let model = Model::compile(&code.to_json(), &logic()).unwrap();
+        let mut context = Context::new();
+        context.set_max_steps(10);
+
+        match model.execute_with(&mut context, vec![Box::new(0 as Integer)]) {
+            Err(Error::IterationLimit {
+                block_type_id: 60_001,
+                limit: 10,
+                ..
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // a loop body which starts its own loop again never completes
+        let code = Code::new()
+            .start(1)
+            .end(2)
+            .value(2, 2, json!(0))
+            .block(
+                3,
+                60_003,
+                &[
+                    (0, "input", "Execution"),
+                    (1, "output", "Execution"),
+                    (2, "input", "Integer"),
+                    (3, "output", "Integer"),
+                    (5, "output", "Execution"),
+                ],
+            )
+            .value(3, 2, json!(5))
+            .connect((1, 0), (3, 0))
+            .connect((3, 1), (3, 0))
+            .connect((3, 5), (2, 0));
+
+        let model = Model::compile(&code.to_json(), &logic()).unwrap();
+        match model.execute_with(&mut context, vec![Box::new(0 as Integer)]) {
+            Err(Error::IterationLimit {
+                block_id: 3,
+                block_type_id: 60_003,
+                limit: 10,
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn values_for_unknown_nodes_are_rejected() {
+        let run = |node_id: u32, execution: bool| {
+            let code = Code::new()
+                .start(1)
+                .block(
+                    3,
+                    14,
+                    &[
+                        (0, "input", "Execution"),
+                        (1, "output", "Execution"),
+                        (3, "output", "Integer"),
+                    ],
+                )
+                .end(2)
+                .connect((1, 0), (3, 0))
+                .connect((3, 1), (2, 0))
+                .connect((3, 3), (2, 2));
+
+            let mut logic = logic();
+            let block = Output {
+                id: 14,
+                node_id,
+                execution,
+            };
+            logic.add_block(Box::new(block)).unwrap();
+
+            let model = Model::compile(&code.to_json(), &logic).unwrap();
+            model.execute(vec![Box::new(0 as Integer)])
+        };
+
+        let values = run(3, false).unwrap().unwrap();
+        assert_eq!(values[0].to_json(), json!(1));
+
+        // the data output 3 is no execution output, node 9 doesn't exist
+        for (node_id, execution) in &[(3, true), (9, true), (9, false)] {
+            match run(*node_id, *execution) {
+                Err(Error::InvalidNextNode {
+                    block_id: 3,
+                    node_id: n,
+                    block_type_id: 14,
+                }) if n == *node_id => {}
+                r => panic!("unexpected result {:?}", r),
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Blocks and a builder for the models used by the tests
+
+use crate::blocks::integer::Integer;
+use crate::error::Result;
+use crate::types::ExecutionType;
+use crate::{ExecutionBlock, Logic, Model, Register};
+use bme_macro::ExecutionBlockHelper;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Start,
+    typ: Start,
+    path: crate,
+
+    fn execute() -> (Integer) {
+        (0)
+    }
+);
+
+ExecutionBlockHelper!(
+    id: 2,
+    name: End,
+    typ: End,
+    path: crate,
+
+    fn execute(_result: Integer) -> () {}
+);
+
+/// The default logic with a start block (1) which has an `Integer` output
+/// and an end block (2) which has an `Integer` input
+pub fn logic() -> Logic {
+    let mut logic = Logic::default();
+    logic.add_block(Box::new(Start {})).unwrap();
+    logic.add_block(Box::new(End {})).unwrap();
+    logic
+}
+
+/// Executes the code with the test logic and the given input for the
+/// start block, returns the inputs of the end block as json. Not reaching
+/// an end block is an error.
+pub fn execute(code: &Code, input: Integer) -> Result<Vec<Value>> {
+    let model = Model::compile(&code.to_json(), &logic())?;
+    let values = model
+        .execute(vec![Box::new(input)])?
+        .ok_or("The execution ended without reaching an end block")?;
+    Ok(values.iter().map(|v| v.to_json()).collect())
+}
+
+/// Executes a single block with the given inputs, returns the values of
+/// its outputs as json
+pub fn execute_block(
+    block: &dyn ExecutionBlock,
+    inputs: Vec<Box<dyn ExecutionType>>,
+) -> Result<Vec<Value>> {
+    let inputs = inputs
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| Register {
+            block_id: 1,
+            node_id: i as u32 * 2 + 2,
+            value,
+        })
+        .collect();
+
+    let outputs = block.execute(inputs, 1)?;
+    Ok(outputs.iter().map(|r| r.value.to_json()).collect())
+}
+
+/// Code of a model in the json form written by the editor
+#[derive(Default)]
+pub struct Code {
+    blocks: Vec<Value>,
+    index: HashMap<u32, usize>,
+}
+
+impl Code {
+    pub fn new() -> Code {
+        Code::default()
+    }
+
+    /// Adds a block with its nodes, given as `(id, io, type)`
+    pub fn block(mut self, block_id: u32, block_type_id: u32, nodes: &[(u32, &str, &str)]) -> Code {
+        let nodes = nodes
+            .iter()
+            .map(|(id, io, typ)| {
+                json!({
+                    "id": id,
+                    "nodeType": io,
+                    "connectionType": typ,
+                    "value": null,
+                    "connections": [],
+                })
+            })
+            .collect::<Vec<_>>();
+
+        self.index.insert(block_id, self.blocks.len());
+        self.blocks.push(json!({
+            "blockId": block_id,
+            "blockTypeId": block_type_id,
+            "nodes": nodes,
+        }));
+        self
+    }
+
+    /// Adds the start block of the test logic
+    pub fn start(self, block_id: u32) -> Code {
+        self.block(
+            block_id,
+            1,
+            &[(0, "output", "Execution"), (3, "output", "Integer")],
+        )
+    }
+
+    /// Adds the end block of the test logic
+    pub fn end(self, block_id: u32) -> Code {
+        self.block(
+            block_id,
+            2,
+            &[(0, "input", "Execution"), (2, "input", "Integer")],
+        )
+    }
+
+    /// Sets the value of an input without a connection
+    pub fn value(mut self, block_id: u32, node_id: u32, value: Value) -> Code {
+        self.node(block_id, node_id)["value"] = value;
+        self
+    }
+
+    /// Connects an output to an input, the connection is added to both nodes
+    /// and has the type of the output
+    pub fn connect(mut self, from: (u32, u32), to: (u32, u32)) -> Code {
+        let typ = self.node(from.0, from.1)["connectionType"].clone();
+        let con = json!({
+            "type": typ,
+            "startBlock": from.0,
+            "startNode": from.1,
+            "endBlock": to.0,
+            "endNode": to.1,
+        });
+
+        for (block_id, node_id) in &[from, to] {
+            let connections = &mut self.node(*block_id, *node_id)["connections"];
+            connections.as_array_mut().unwrap().push(con.clone());
+        }
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        json!(self.blocks).to_string()
+    }
+
+    fn node(&mut self, block_id: u32, node_id: u32) -> &mut Value {
+        let missing = || panic!("The node {} of block {} is missing", node_id, block_id);
+        let block = match self.index.get(&block_id) {
+            Some(&i) => &mut self.blocks[i],
+            None => missing(),
+        };
+
+        match block["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|n| n["id"] == node_id)
+        {
+            Some(node) => node,
+            None => missing(),
+        }
+    }
+}
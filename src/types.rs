@@ -1,11 +1,18 @@
-pub trait ExecutionType: downcast_rs::Downcast + std::fmt::Debug {
-    fn get_name(&self) -> &'static str;
+pub trait ExecutionType: downcast_rs::Downcast + std::fmt::Debug + Send + Sync {
+    fn get_name(&self) -> &str;
 
-    fn from_json(&self, json: serde_json::Value) -> Box<ExecutionType>;
+    /// Creates a new value of this type out of json. Values which
+    /// can't be parsed into the type return an error.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_json(&self, json: serde_json::Value) -> crate::error::Result<Box<dyn ExecutionType>>;
 
-    fn duplicate(&self) -> Box<ExecutionType>;
+    /// Converts the value into json, so `from_json` creates the same value
+    /// again. Lists keep this for values of the built-in types only.
+    fn to_json(&self) -> serde_json::Value;
 
-    fn get_color(&self) -> &'static str {
+    fn duplicate(&self) -> Box<dyn ExecutionType>;
+
+    fn get_color(&self) -> &str {
         "blue"
     }
 
@@ -13,6 +20,11 @@ pub trait ExecutionType: downcast_rs::Downcast + std::fmt::Debug {
         None
     }
 
+    /// Regex the editor uses to check the values typed in for this type
+    fn get_value_check(&self) -> Option<&'static str> {
+        None
+    }
+
     fn get_multi_output(&self) -> bool {
         false
     }
@@ -20,7 +32,7 @@ pub trait ExecutionType: downcast_rs::Downcast + std::fmt::Debug {
 downcast_rs::impl_downcast!(ExecutionType);
 
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct Execution {}
 
 impl Execution {
@@ -34,11 +46,15 @@ impl ExecutionType for Execution {
         "Execution"
     }
 
-    fn from_json(&self, _json: serde_json::Value) -> Box<ExecutionType> {
-        Box::new(Execution {})
+    fn from_json(&self, _json: serde_json::Value) -> crate::error::Result<Box<dyn ExecutionType>> {
+        Ok(Box::new(Execution {}))
     }
 
-    fn duplicate(&self) -> Box<ExecutionType> {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    fn duplicate(&self) -> Box<dyn ExecutionType> {
         Box::new(self.clone())
     }
 
@@ -50,3 +66,150 @@ impl ExecutionType for Execution {
         None
     }
 }
+
+
+/// Selects the execution output of a normal block which is run next.
+///
+/// The index refers to the names returned by `ExecutionBlock::get_next_outputs`.
+/// Returning it from a block defined by the `ExecutionBlockHelper` makro
+/// generates the matching `Execution` value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Next(pub usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::date::{Date, DateTime, Duration};
+    use crate::blocks::decimal::Decimal;
+    use crate::blocks::float_vec3::FloatVector3;
+    use crate::blocks::list::List;
+    use crate::Logic;
+
+    #[test]
+    fn values_round_trip_through_json() {
+        let logic = Logic::default();
+        let values: Vec<Box<dyn ExecutionType>> = vec![
+            Box::new(String::from("text")),
+            Box::new(-7i64),
+            Box::new(1.25f64),
+            Box::new(f64::NAN),
+            Box::new(f64::INFINITY),
+            Box::new(f64::NEG_INFINITY),
+            Box::new(true),
+            Box::new(FloatVector3::new(1.0, f64::NAN, f64::NEG_INFINITY)),
+            Box::new(List(vec![Box::new(1i64), Box::new(String::from("a"))])),
+            Box::new(List(vec![
+                Box::new(f64::NAN),
+                Box::new(Decimal::new(5, 1)),
+                Box::new(FloatVector3::new(1.0, 2.0, f64::INFINITY)),
+                Box::new(Date::new(-800_000)),
+                Box::new(List(vec![Box::new(DateTime::new(0))])),
+            ])),
+            Box::new(Decimal::new(-12_345, 2)),
+            Box::new(Date::new(0)),
+            Box::new(Date::new(-800_000)),
+            Box::new(Date::new(3_000_000)),
+            Box::new(DateTime::new(-1)),
+            Box::new(DateTime::new(100_000_000 * 86_400_000)),
+            Box::new(Duration::new(-90_061_001)),
+        ];
+
+        for value in values {
+            let json = value.to_json();
+            let parsed = logic
+                .type_from_json(value.get_name().to_string(), json.clone())
+                .unwrap_or_else(|e| panic!("{} can't be read back: {}", json, e));
+
+            assert_eq!(parsed.to_json(), json);
+            assert_eq!(parsed.get_name(), value.get_name());
+        }
+    }
+
+    #[test]
+    fn value_checks_agree_with_the_parsers() {
+        let logic = Logic::default();
+        let values: &[(&str, &[&str], &[&str])] = &[
+            (
+                "Integer",
+                &["0", " -12 ", "+7"],
+                &["", "1.5", "1e3", "- 1", "a"],
+            ),
+            (
+                "Float",
+                &[
+                    "0",
+                    "-1.5",
+                    ".5",
+                    "1.",
+                    "+1",
+                    "2e-3",
+                    "1.5E+4",
+                    "NaN",
+                    "inf",
+                    "-inf",
+                    "+Infinity",
+                    " nan ",
+                ],
+                &["", ".", "1e", "e1", "--1", "1.2.3", "infinit", "1,5"],
+            ),
+            ("Boolean", &["true", " false "], &["", "True", "1", "yes"]),
+            (
+                "FloatVector3",
+                &[
+                    "0.0,0.0,0.0",
+                    " -1 , .5 , 2e3 ",
+                    "NaN,inf,-inf",
+                    "[1, -2.5, 3e2]",
+                    r#"[1, "NaN", " -inf "]"#,
+                    r#"{"x": 1, "y": "inf", "z": -0.5}"#,
+                    r#"{"z":3,"x":1,"y":2}"#,
+                ],
+                &[
+                    "",
+                    "1,2",
+                    "1,2,3,4",
+                    "1,,2",
+                    "[1, 2]",
+                    "[1, 2, 3",
+                    "[1, 2, a]",
+                    r#"{"x": 1, "y": 2}"#,
+                    r#"{"x": 1, "y": 2, "w": 3}"#,
+                ],
+            ),
+            (
+                "Decimal",
+                &["0", "-12.340", "+1", "1.", "1.5e3", "15E-4"],
+                &["", "-", ".5", "1.2.3", "1e", "NaN"],
+            ),
+        ];
+
+        for (typ, valid, invalid) in values {
+            let check = logic
+                .get_connection_type(typ)
+                .and_then(|t| t.get_value_check())
+                .unwrap();
+            let check = regex::Regex::new(check).unwrap();
+
+            for (texts, expected) in &[(valid, true), (invalid, false)] {
+                for text in texts.iter() {
+                    let json = serde_json::Value::String(text.to_string());
+
+                    assert_eq!(
+                        check.is_match(text),
+                        *expected,
+                        "{} check of {:?}",
+                        typ,
+                        text
+                    );
+                    assert_eq!(
+                        logic.type_from_json(typ.to_string(), json).is_ok(),
+                        *expected,
+                        "{} parser of {:?}",
+                        typ,
+                        text
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use crate::types::ExecutionType;
+use crate::{Block, ExecutionBlockType, Logic, Node};
+use std::collections::{HashMap, HashSet};
+
+/// Values of the inputs without a connection, by their block and node id
+pub(crate) type Literals = HashMap<(u32, u32), Box<dyn ExecutionType>>;
+
+/// A single rule violation found while validating the code of a model
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    pub block_id: Option<u32>,
+    pub node_id: Option<u32>,
+    pub kind: ViolationKind,
+}
+
+/// The different rules a model can violate
+#[derive(Debug, PartialEq, Clone)]
+pub enum ViolationKind {
+    NoStartBlock,
+    MultipleStartBlocks,
+    DuplicateBlockId,
+    UnknownBlockType {
+        block_type_id: u32,
+    },
+    UnknownNode,
+    DuplicateNodeId,
+    MissingInput,
+    NodeMismatch {
+        expected_io: String,
+        expected_type: String,
+    },
+    InvalidConnection,
+    MissingConnectionBlock {
+        block_id: u32,
+    },
+    MissingConnectionNode {
+        block_id: u32,
+        node_id: u32,
+    },
+    ConnectionTypeMismatch {
+        expected: String,
+        found: String,
+    },
+    MultipleExecutionConnections,
+    MultipleInputConnections,
+    StaticCycle,
+    InvalidValue {
+        message: String,
+    },
+}
+
+impl Violation {
+    fn new(block_id: Option<u32>, node_id: Option<u32>, kind: ViolationKind) -> Violation {
+        Violation {
+            block_id,
+            node_id,
+            kind,
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(b) = self.block_id {
+            write!(f, "Block {}", b)?;
+
+            if let Some(n) = self.node_id {
+                write!(f, ", node {}", n)?;
+            }
+
+            write!(f, ": ")?;
+        }
+
+        match &self.kind {
+            ViolationKind::NoStartBlock => write!(f, "No start block available"),
+            ViolationKind::MultipleStartBlocks => write!(f, "Only one start block is allowed"),
+            ViolationKind::DuplicateBlockId => write!(f, "The block id is used more than once"),
+            ViolationKind::UnknownBlockType { block_type_id } => {
+                write!(f, "The block type {} is not available", block_type_id)
+            }
+            ViolationKind::UnknownNode => write!(f, "The node is not defined by the block type"),
+            ViolationKind::DuplicateNodeId => {
+                write!(f, "The node id is used more than once within the block")
+            }
+            ViolationKind::MissingInput => write!(f, "The input of the block type is missing"),
+            ViolationKind::NodeMismatch {
+                expected_io,
+                expected_type,
+            } => write!(
+                f,
+                "The node should be an {} of type {}",
+                expected_io, expected_type
+            ),
+            ViolationKind::InvalidConnection => {
+                write!(f, "The connection doesn't belong to this node")
+            }
+            ViolationKind::MissingConnectionBlock { block_id } => {
+                write!(f, "The connected block {} doesn't exist", block_id)
+            }
+            ViolationKind::MissingConnectionNode { block_id, node_id } => write!(
+                f,
+                "The connected node {} of block {} doesn't exist",
+                node_id, block_id
+            ),
+            ViolationKind::ConnectionTypeMismatch { expected, found } => write!(
+                f,
+                "The connection type {} doesn't match the node type {}",
+                found, expected
+            ),
+            ViolationKind::MultipleExecutionConnections => {
+                write!(f, "An execution output can only have one connection")
+            }
+            ViolationKind::MultipleInputConnections => {
+                write!(f, "An input can only have one connection")
+            }
+            ViolationKind::StaticCycle => {
+                write!(f, "The static block depends on its own output")
+            }
+            ViolationKind::InvalidValue { message } => {
+                write!(f, "The value is not valid: {}", message)
+            }
+        }
+    }
+}
+
+/// Checks the parsed code against the logic and returns every violation found,
+/// together with the values of the inputs without a connection
+pub(crate) fn validate(code: &[Block], logic: &Logic) -> (Vec<Violation>, Literals) {
+    let mut violations = vec![];
+    let mut literals = HashMap::new();
+    let mut block_ids = HashSet::new();
+    let mut start_blocks = 0;
+
+    // all blocks and nodes of the code by their ids
+    let known_blocks = code.iter().map(|b| b.block_id).collect::<HashSet<u32>>();
+    let mut nodes = HashMap::new();
+    for block in code {
+        for node in &block.nodes {
+            nodes.entry((block.block_id, node.id)).or_insert(node);
+        }
+    }
+
+    for block in code {
+        if !block_ids.insert(block.block_id) {
+            violations.push(Violation::new(
+                Some(block.block_id),
+                None,
+                ViolationKind::DuplicateBlockId,
+            ));
+        }
+
+        // get the block definition from the logic
+        let exec_block = match logic.get_block(block.block_type_id) {
+            Some(b) => b,
+            None => {
+                violations.push(Violation::new(
+                    Some(block.block_id),
+                    None,
+                    ViolationKind::UnknownBlockType {
+                        block_type_id: block.block_type_id,
+                    },
+                ));
+                continue;
+            }
+        };
+
+        if exec_block.get_type() == ExecutionBlockType::Start {
+            start_blocks += 1;
+
+            if start_blocks > 1 {
+                violations.push(Violation::new(
+                    Some(block.block_id),
+                    None,
+                    ViolationKind::MultipleStartBlocks,
+                ));
+            }
+        }
+
+        let definitions = exec_block.get_nodes();
+
+        // every data input of the block type needs a node
+        for d in &definitions {
+            if d.io == "input" && d.typ != "Execution" && !block.nodes.iter().any(|n| n.id == d.id)
+            {
+                violations.push(Violation::new(
+                    Some(block.block_id),
+                    Some(d.id),
+                    ViolationKind::MissingInput,
+                ));
+            }
+        }
+
+        let mut node_ids = HashSet::new();
+
+        for node in &block.nodes {
+            let violation = |kind| Violation::new(Some(block.block_id), Some(node.id), kind);
+
+            // connections to a node id used twice can't be resolved
+            if !node_ids.insert(node.id) {
+                violations.push(violation(ViolationKind::DuplicateNodeId));
+            }
+
+            // check the node against the definition of the block type
+            match definitions.iter().find(|d| d.id == node.id) {
+                None => violations.push(violation(ViolationKind::UnknownNode)),
+                Some(d) if d.io != node.node_type || d.typ != node.connection_type => violations
+                    .push(violation(ViolationKind::NodeMismatch {
+                        expected_io: d.io.to_string(),
+                        expected_type: d.typ.to_string(),
+                    })),
+                Some(_) => {}
+            }
+
+            // check the number of connections
+            if node.node_type == "output"
+                && node.connection_type == "Execution"
+                && node.connections.len() > 1
+            {
+                violations.push(violation(ViolationKind::MultipleExecutionConnections));
+            }
+
+            if node.node_type == "input"
+                && node.connection_type != "Execution"
+                && node.connections.len() > 1
+            {
+                violations.push(violation(ViolationKind::MultipleInputConnections));
+            }
+
+            // check the value of inputs without a connection
+            if node.node_type == "input"
+                && node.connection_type != "Execution"
+                && node.connections.is_empty()
+                && logic.has_connection_type(&node.connection_type)
+            {
+                match logic.type_from_json(node.connection_type.clone(), node.value.clone()) {
+                    Ok(value) => {
+                        literals.insert((block.block_id, node.id), value);
+                    }
+                    Err(e) => violations.push(violation(ViolationKind::InvalidValue {
+                        message: e.to_string(),
+                    })),
+                }
+            }
+
+            // check every connection of the node
+            for con in &node.connections {
+                let (own, other, other_io) = if node.node_type == "output" {
+                    (
+                        (con.start_block, con.start_node),
+                        (con.end_block, con.end_node),
+                        "input",
+                    )
+                } else {
+                    (
+                        (con.end_block, con.end_node),
+                        (con.start_block, con.start_node),
+                        "output",
+                    )
+                };
+
+                if own != (block.block_id, node.id) {
+                    violations.push(violation(ViolationKind::InvalidConnection));
+                    continue;
+                }
+
+                let other_node = match find_node(&nodes, &known_blocks, other.0, other.1) {
+                    Ok(n) => n,
+                    Err(kind) => {
+                        violations.push(violation(kind));
+                        continue;
+                    }
+                };
+
+                if other_node.node_type != other_io {
+                    violations.push(violation(ViolationKind::InvalidConnection));
+                }
+
+                for typ in &[&con.typ, &other_node.connection_type] {
+                    if **typ != node.connection_type {
+                        violations.push(violation(ViolationKind::ConnectionTypeMismatch {
+                            expected: node.connection_type.clone(),
+                            found: typ.to_string(),
+                        }));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    if start_blocks == 0 {
+        violations.push(Violation::new(None, None, ViolationKind::NoStartBlock));
+    }
+
+    (violations, literals)
+}
+
+fn find_node<'a>(
+    nodes: &HashMap<(u32, u32), &'a Node>,
+    block_ids: &HashSet<u32>,
+    block_id: u32,
+    node_id: u32,
+) -> Result<&'a Node, ViolationKind> {
+    match nodes.get(&(block_id, node_id)) {
+        Some(n) => Ok(n),
+        None if block_ids.contains(&block_id) => {
+            Err(ViolationKind::MissingConnectionNode { block_id, node_id })
+        }
+        None => Err(ViolationKind::MissingConnectionBlock { block_id }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{logic, Code};
+    use serde_json::json;
+
+    fn validate_code(code: Code) -> Vec<Violation> {
+        let code: Vec<Block> = serde_json::from_str(&code.to_json()).unwrap();
+        validate(&code, &logic()).0
+    }
+
+    fn start_end() -> Code {
+        Code::new().start(1).end(2).connect((1, 0), (2, 0))
+    }
+
+    #[test]
+    fn valid_model() {
+        let code = start_end().connect((1, 3), (2, 2));
+        assert_eq!(validate_code(code), vec![]);
+    }
+
+    #[test]
+    fn no_start_block() {
+        let code = Code::new().end(2).value(2, 2, json!(1));
+
+        assert_eq!(
+            validate_code(code),
+            vec![Violation::new(None, None, ViolationKind::NoStartBlock)]
+        );
+    }
+
+    #[test]
+    fn unknown_and_duplicate_blocks() {
+        let code = start_end()
+            .value(2, 2, json!(1))
+            .block(2, 99, &[])
+            .block(3, 1, &[]);
+
+        assert_eq!(
+            validate_code(code),
+            vec![
+                Violation::new(Some(2), None, ViolationKind::DuplicateBlockId),
+                Violation::new(
+                    Some(2),
+                    None,
+                    ViolationKind::UnknownBlockType { block_type_id: 99 }
+                ),
+                Violation::new(Some(3), None, ViolationKind::MultipleStartBlocks),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_and_unknown_nodes() {
+        let code = Code::new()
+            .block(
+                1,
+                1,
+                &[(0, "output", "Execution"), (5, "output", "Integer")],
+            )
+            .block(2, 2, &[(0, "input", "Execution"), (2, "output", "Integer")]);
+
+        assert_eq!(
+            validate_code(code),
+            vec![
+                Violation::new(Some(1), Some(5), ViolationKind::UnknownNode),
+                Violation::new(
+                    Some(2),
+                    Some(2),
+                    ViolationKind::NodeMismatch {
+                        expected_io: "input".to_string(),
+                        expected_type: "Integer".to_string(),
+                    }
+                ),
+            ]
+        );
+
+        let code = Code::new()
+            .block(1, 1, &[(0, "output", "Execution")])
+            .block(2, 2, &[(0, "input", "Execution")]);
+
+        assert_eq!(
+            validate_code(code),
+            vec![Violation::new(
+                Some(2),
+                Some(2),
+                ViolationKind::MissingInput
+            )]
+        );
+    }
+
+    #[test]
+    fn duplicate_nodes() {
+        let code = start_end().connect((1, 3), (2, 2)).block(
+            3,
+            64_001,
+            &[
+                (2, "input", "Integer"),
+                (3, "output", "Integer"),
+                (3, "output", "Integer"),
+                (4, "input", "Integer"),
+            ],
+        );
+        let code = code.value(3, 2, json!(1)).value(3, 4, json!(2));
+
+        assert_eq!(
+            validate_code(code),
+            vec![Violation::new(
+                Some(3),
+                Some(3),
+                ViolationKind::DuplicateNodeId
+            )]
+        );
+    }
+
+    #[test]
+    fn invalid_connections_and_values() {
+        // the integer of the start block can't be printed
+        let code = start_end()
+            .value(2, 2, json!("one"))
+            .block(
+                3,
+                60_001,
+                &[(0, "input", "Execution"), (2, "input", "String")],
+            )
+            .connect((1, 3), (3, 2));
+
+        let violations = validate_code(code);
+        let mismatch = |block_id, node_id, expected: &str, found: &str| {
+            Violation::new(
+                Some(block_id),
+                Some(node_id),
+                ViolationKind::ConnectionTypeMismatch {
+                    expected: expected.to_string(),
+                    found: found.to_string(),
+                },
+            )
+        };
+
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0], mismatch(1, 3, "Integer", "String"));
+        assert!(matches!(
+            violations[1].kind,
+            ViolationKind::InvalidValue { .. }
+        ));
+        assert_eq!(violations[2], mismatch(3, 2, "String", "Integer"));
+    }
+}
@@ -0,0 +1,6 @@
+#[bme::block(id = 1)]
+async fn double(value: i64) -> i64 {
+    value * 2
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct Counter;
+
+impl Counter {
+    #[bme::block(id = 1)]
+    fn double(&self, value: i64) -> i64 {
+        value * 2
+    }
+}
+
+fn main() {}
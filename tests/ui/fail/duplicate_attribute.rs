@@ -0,0 +1,13 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Double,
+    id: 2,
+
+    fn execute(value: i64) -> (i64) {
+        (value * 2)
+    }
+);
+
+fn main() {}
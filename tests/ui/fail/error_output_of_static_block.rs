@@ -0,0 +1,14 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: ParseInteger,
+    typ: Static,
+    error_output: true,
+
+    fn execute(text: String) -> Result<(i64), std::num::ParseIntError> {
+        text.parse()
+    }
+);
+
+fn main() {}
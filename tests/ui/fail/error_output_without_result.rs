@@ -0,0 +1,14 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Double,
+    typ: Normal,
+    error_output: true,
+
+    fn execute(value: i64) -> (i64) {
+        (value * 2)
+    }
+);
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[bme::block(id = 1)]
+fn identity<T>(value: T) -> T {
+    value
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use bme::ExecutionType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, ExecutionType)]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Forever,
+    typ: Loop,
+
+    fn execute(value: i64) -> (i64) {
+        (value)
+    }
+);
+
+fn main() {}
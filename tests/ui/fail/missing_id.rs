@@ -0,0 +1,11 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    name: Double,
+
+    fn execute(value: i64) -> (i64) {
+        (value * 2)
+    }
+);
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[bme::block(typ = Static)]
+fn double(value: i64) -> i64 {
+    value * 2
+}
+
+fn main() {}
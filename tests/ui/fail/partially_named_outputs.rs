@@ -0,0 +1,12 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Split,
+
+    fn execute(value: i64) -> (half: i64, i64) {
+        (value / 2, value % 2)
+    }
+);
+
+fn main() {}
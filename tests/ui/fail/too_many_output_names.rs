@@ -0,0 +1,6 @@
+#[bme::block(id = 1, outputs = [quotient, rest])]
+fn halve(value: i64) -> i64 {
+    value / 2
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Double,
+
+    fn execute(#[pin(color = "red")] value: i64) -> (i64) {
+        (value * 2)
+    }
+);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use bme::ExecutionType;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, ExecutionType)]
+#[execution_type(colour = "orange")]
+pub struct Customer {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bme::ExecutionBlockHelper;
+
+ExecutionBlockHelper!(
+    id: 1,
+    name: Double,
+
+    fn execute(value: i64) -> (#[pin(label = "Double")] i64) {
+        (value * 2)
+    }
+);
+
+fn main() {}
@@ -0,0 +1,61 @@
+use bme::{ExecutionBlock, ExecutionBlockType, Register};
+
+/// Divides two integers
+///
+/// Gives the quotient and the rest.
+#[bme::block(id = 1, typ = Static, outputs = [quotient, rest])]
+fn integer_divide_with_rest(#[pin(label = "Dividend")] value: i64, divisor: i64) -> (i64, i64) {
+    (value / divisor, value % divisor)
+}
+
+#[bme::block(id = 2, typ = Normal, pure = false)]
+fn print_text(text: String) {
+    println!("{}", text);
+}
+
+fn main() {
+    // the function stays callable as it is
+    assert_eq!(integer_divide_with_rest(7, 2), (3, 1));
+
+    let block = IntegerDivideWithRest {};
+    assert_eq!(block.get_id(), 1);
+    assert_eq!(block.get_name(), "IntegerDivideWithRest");
+    assert_eq!(block.get_type(), ExecutionBlockType::Static);
+    assert_eq!(
+        block.get_description(),
+        Some("Divides two integers\n\nGives the quotient and the rest.")
+    );
+    assert_eq!(block.get_inputs(), &["i64", "i64"]);
+    assert_eq!(block.get_outputs(), &["i64", "i64"]);
+
+    let pins = block.get_input_pins();
+    assert_eq!(pins[0].name, "value");
+    assert_eq!(pins[0].label, Some("Dividend"));
+    assert_eq!(pins[1].name, "divisor");
+    let pins = block.get_output_pins();
+    assert_eq!(pins[0].name, "quotient");
+    assert_eq!(pins[1].name, "rest");
+
+    let inputs = vec![
+        Register {
+            block_id: 7,
+            node_id: 2,
+            value: Box::new(7i64),
+        },
+        Register {
+            block_id: 7,
+            node_id: 4,
+            value: Box::new(2i64),
+        },
+    ];
+    let outputs = block.execute(inputs, 7).unwrap();
+    assert_eq!(outputs[0].value.downcast_ref::<i64>(), Some(&3));
+    assert_eq!(outputs[1].value.downcast_ref::<i64>(), Some(&1));
+
+    let block = PrintText {};
+    assert_eq!(block.get_name(), "PrintText");
+    assert_eq!(block.get_type(), ExecutionBlockType::Normal);
+    assert_eq!(block.get_description(), None);
+    assert!(!block.is_pure());
+    assert!(block.get_outputs().is_empty());
+}
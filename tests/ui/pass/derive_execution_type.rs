@@ -0,0 +1,57 @@
+use bme::types::ExecutionType;
+use bme::{ExecutionType, Logic};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ExecutionType)]
+#[execution_type(color = "orange", edit_default = "{\"name\": \"\"}", multi_output)]
+pub struct Customer {
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ExecutionType)]
+#[execution_type(name = "Id", value_check = r"^[0-9]+$")]
+pub struct CustomerId(u32);
+
+#[derive(Debug, Clone, Serialize, Deserialize, ExecutionType)]
+pub struct Distances(HashMap<(u32, u32), f64>);
+
+fn main() {
+    let customer = Customer {
+        name: String::from("Ada"),
+    };
+    assert_eq!(customer.get_name(), "Customer");
+    assert_eq!(customer.get_color(), "orange");
+    assert_eq!(customer.get_edit_default(), Some("{\"name\": \"\"}"));
+    assert_eq!(customer.get_value_check(), None);
+    assert!(customer.get_multi_output());
+    assert_eq!(customer.to_json(), json!({ "name": "Ada" }));
+
+    // values are read from json and from the json text of the editor
+    for value in &[json!({ "name": "Ada" }), json!("{\"name\": \"Ada\"}")] {
+        let value = customer.from_json(value.clone()).unwrap();
+        assert_eq!(value.downcast_ref::<Customer>(), Some(&customer));
+    }
+    assert!(customer.from_json(json!({ "id": 1 })).is_err());
+
+    let id = CustomerId(7);
+    assert_eq!(id.get_name(), "Id");
+    assert_eq!(id.get_value_check(), Some("^[0-9]+$"));
+    assert!(!id.get_multi_output());
+    assert_eq!(id.from_json(json!("12")).unwrap().to_json(), json!(12));
+
+    let mut logic = Logic::default();
+    logic.add_connection_type(Box::new(id));
+    assert!(logic.has_connection_type("Id"));
+    let value = logic.type_from_json(String::from("Id"), json!(3)).unwrap();
+    assert_eq!(value.get_name(), "Id");
+
+    // values serde can't convert into json panic instead of becoming null
+    let distances = Distances(vec![((1, 2), 0.5)].into_iter().collect());
+    std::panic::set_hook(Box::new(|_| {}));
+    let panic = std::panic::catch_unwind(|| distances.to_json()).unwrap_err();
+    let _ = std::panic::take_hook();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("The Distances value can't be converted into json"));
+}
@@ -0,0 +1,45 @@
+use bme::error::Error;
+use bme::{ExecutionBlock, Register};
+
+// errors of other types are converted through their `Display` implementation
+#[bme::block(id = 1)]
+fn parse_integer(text: String) -> Result<i64, std::num::ParseIntError> {
+    text.parse()
+}
+
+// errors of the crate are kept as they are
+#[bme::block(id = 2)]
+fn increment(value: i64) -> bme::error::Result<i64> {
+    value.checked_add(1).ok_or(Error::Overflow)
+}
+
+fn main() {
+    let input = vec![Register {
+        block_id: 7,
+        node_id: 3,
+        value: Box::new(String::from("x")),
+    }];
+
+    match (ParseInteger {}).execute(input, 7) {
+        Err(Error::BlockFailed {
+            block_id: 7,
+            block_type_id: 1,
+            source,
+        }) => match *source {
+            Error::Message(message) => assert_eq!(message, "invalid digit found in string"),
+            e => panic!("unexpected error {:?}", e),
+        },
+        r => panic!("unexpected result {:?}", r),
+    }
+
+    let input = vec![Register {
+        block_id: 7,
+        node_id: 3,
+        value: Box::new(i64::MAX),
+    }];
+
+    match (Increment {}).execute(input, 8) {
+        Err(Error::BlockFailed { source, .. }) => assert!(matches!(*source, Error::Overflow)),
+        r => panic!("unexpected result {:?}", r),
+    }
+}